@@ -0,0 +1,213 @@
+use crate::format::{decode_map, MapFormat};
+use crate::grid::{Grid, ParseOptions};
+use crate::solver::{Algorithm, Heuristic, MaxPathDfs, PathResult, SearchBudget, Solver};
+use crate::topology::Movement;
+use crate::Coord;
+use rayon::prelude::*;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+/// Réglages appliqués à chaque carte du lot
+#[derive(Copy, Clone, Debug)]
+pub struct BatchOptions {
+    pub parse: ParseOptions,
+    pub movement: Movement,
+    pub algorithm: Algorithm,
+    pub heuristic: Heuristic,
+    // Budget de la recherche du chemin maximal, None pour ne chercher que le minimal
+    pub max_path: Option<SearchBudget>,
+    pub start: Option<Coord>,
+    pub end: Option<Coord>,
+}
+
+#[derive(Serialize)]
+pub struct PathSummary {
+    pub cost: i64,
+    pub length: usize,
+    pub optimal: bool,
+    pub algorithm: &'static str,
+    pub nodes_expanded: u64,
+    pub elapsed_ms: f64,
+}
+
+#[derive(Serialize)]
+pub struct FileReport {
+    pub file: String,
+    // None si la carte n'a pas pu être lue ou résolue
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<&'static str>,
+    pub rows: usize,
+    pub cols: usize,
+    pub min: Option<PathSummary>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max: Option<PathSummary>,
+}
+
+#[derive(Serialize)]
+pub struct BatchReport {
+    pub files: usize,
+    pub solved: usize,
+    pub failed: usize,
+    pub jobs: usize,
+    pub elapsed_ms: f64,
+    pub results: Vec<FileReport>,
+}
+
+fn is_map_file(path: &Path) -> bool {
+    path.is_file() && MapFormat::from_extension(path).is_some()
+}
+
+/// Liste les cartes désignées par chaque entrée : un dossier (ses fichiers aux extensions de carte connues),
+/// un motif glob (tous les fichiers correspondants) ou un simple fichier. L'ordre est celui des noms.
+pub fn collect_inputs(inputs: &[String]) -> Result<Vec<PathBuf>, String> {
+    let mut files = Vec::new();
+    for input in inputs {
+        let path = Path::new(input);
+        let mut found: Vec<PathBuf> = if path.is_dir() {
+            let entries = fs::read_dir(path).map_err(|e| format!("cannot read directory {}: {}", input, e))?;
+            entries.filter_map(|entry| entry.ok().map(|entry| entry.path())).filter(|p| is_map_file(p)).collect()
+        } else if path.is_file() {
+            vec![path.to_path_buf()]
+        } else {
+            let paths = glob::glob(input).map_err(|e| format!("invalid pattern {}: {}", input, e))?;
+            paths.filter_map(Result::ok).filter(|p| p.is_file()).collect()
+        };
+        if found.is_empty() {
+            return Err(format!("no map file matches {}", input));
+        }
+        found.sort();
+        files.extend(found);
+    }
+    Ok(files)
+}
+
+fn summary(result: PathResult, algorithm: &'static str, started: Instant) -> PathSummary {
+    PathSummary {
+        cost: result.cost,
+        length: result.path.len(),
+        optimal: result.optimal,
+        algorithm,
+        nodes_expanded: result.nodes_expanded,
+        elapsed_ms: started.elapsed().as_secs_f64() * 1000.0,
+    }
+}
+
+fn solve_grid(grid: &Grid, options: &BatchOptions) -> Result<(Option<PathSummary>, Option<PathSummary>), String> {
+    let start = options.start.unwrap_or((0, 0));
+    let end = options.end.unwrap_or((grid.rows() - 1, grid.cols() - 1));
+    for (r, c) in [start, end] {
+        if !grid.contains((r, c)) {
+            return Err(format!("Coordinate ({},{}) is outside the {}x{} grid", r, c, grid.rows(), grid.cols()));
+        }
+    }
+    if grid.is_wall(start) || grid.is_wall(end) {
+        return Ok((None, None));
+    }
+
+    let movement = options.movement;
+    let solver = options.algorithm.for_grid(grid, movement, options.heuristic)?;
+    let started = Instant::now();
+    let min = solver.try_solve(grid, start, end, &mut ()).map_err(|cycle| format!("No minimum cost path: {}", cycle))?;
+    let min = min.map(|result| summary(result, solver.name(), started));
+
+    let max = options.max_path.and_then(|budget| {
+        let solver = MaxPathDfs { movement, budget };
+        let started = Instant::now();
+        solver.solve(grid, start, end, &mut ()).map(|result| summary(result, solver.name(), started))
+    });
+    Ok((min, max))
+}
+
+/// Lit et résout une carte ; toute erreur est consignée dans le rapport au lieu d'être propagée
+pub fn solve_file(path: &Path, options: &BatchOptions) -> FileReport {
+    let mut report = FileReport {
+        file: path.display().to_string(),
+        error: None,
+        format: None,
+        rows: 0,
+        cols: 0,
+        min: None,
+        max: None,
+    };
+    let data = match fs::read(path) {
+        Ok(data) => data,
+        Err(e) => {
+            report.error = Some(e.to_string());
+            return report;
+        }
+    };
+    let format = MapFormat::detect(path, &data);
+    report.format = Some(format.name());
+    let grid = match decode_map(&data, format, options.parse) {
+        Ok(grid) => grid,
+        Err(e) => {
+            report.error = Some(e.to_string());
+            return report;
+        }
+    };
+    report.rows = grid.rows();
+    report.cols = grid.cols();
+    match solve_grid(&grid, options) {
+        Ok((min, max)) => {
+            report.min = min;
+            report.max = max;
+        }
+        Err(e) => report.error = Some(e),
+    }
+    report
+}
+
+/// Résout toutes les cartes sur `jobs` threads ; les résultats gardent l'ordre de `files`.
+pub fn run_batch(files: &[PathBuf], jobs: usize, options: &BatchOptions) -> Result<BatchReport, String> {
+    let pool = rayon::ThreadPoolBuilder::new().num_threads(jobs).build().map_err(|e| e.to_string())?;
+    let started = Instant::now();
+    let results: Vec<FileReport> = pool.install(|| files.par_iter().map(|path| solve_file(path, options)).collect());
+    Ok(BatchReport {
+        files: results.len(),
+        solved: results.iter().filter(|r| r.min.is_some()).count(),
+        failed: results.iter().filter(|r| r.error.is_some()).count(),
+        jobs: pool.current_num_threads(),
+        elapsed_ms: started.elapsed().as_secs_f64() * 1000.0,
+        results,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::topology::Topology;
+
+    #[test]
+    fn corrupt_map_is_reported_without_stopping_the_batch() {
+        let dir = std::env::temp_dir().join(format!("hexpath-batch-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("bad.pgm"), "P5 4294967296 4294967297 255\n").unwrap();
+        fs::write(dir.join("huge.pgm"), "P2 100000 100000 255\n1 2").unwrap();
+        fs::write(dir.join("good.txt"), "01 02\n03 04\n").unwrap();
+
+        let options = BatchOptions {
+            parse: ParseOptions::default(),
+            movement: Movement { topology: Topology::Four, diagonal_cost: 1.0 },
+            algorithm: Algorithm::Dijkstra,
+            heuristic: Heuristic::Manhattan,
+            max_path: None,
+            start: None,
+            end: None,
+        };
+        let files = collect_inputs(&[dir.display().to_string()]).unwrap();
+        let report = run_batch(&files, 2, &options).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!((report.files, report.solved, report.failed), (3, 1, 2));
+        let result = |name: &str| report.results.iter().find(|r| r.file.ends_with(name)).unwrap();
+        assert_eq!(result("good.txt").min.as_ref().map(|min| min.cost), Some(6));
+        for name in ["bad.pgm", "huge.pgm"] {
+            assert!(result(name).error.is_some(), "{} should be reported as an error", name);
+            assert!(result(name).min.is_none());
+        }
+    }
+}
@@ -0,0 +1,262 @@
+use crate::generate::{generate_map, Terrain};
+use crate::solver::{Dijkstra, GreedyMaxPath, MaxPathDfs, PathResult, SearchBudget, Solver};
+use crate::topology::{Movement, Topology};
+use serde::Serialize;
+use std::time::Instant;
+
+/// Paramètres communs à toutes les tailles mesurées
+#[derive(Copy, Clone, Debug)]
+pub struct BenchOptions {
+    pub runs: usize,
+    pub seed: u64,
+    pub movement: Movement,
+    pub budget: SearchBudget,
+    pub wall_density: f64,
+    pub terrain: Terrain,
+}
+
+#[derive(Serialize)]
+pub struct SolverTiming {
+    pub algorithm: &'static str,
+    // Nombre d'exécutions ayant trouvé un chemin, et prouvé optimal
+    pub solved: usize,
+    pub optimal: usize,
+    pub median_ms: f64,
+    pub p95_ms: f64,
+    pub median_nodes: u64,
+    pub p95_nodes: u64,
+}
+
+/// Rapport coût glouton / coût exact du chemin maximal
+#[derive(Serialize)]
+pub struct GreedyRatio {
+    pub mean: f64,
+    pub min: f64,
+    pub max: f64,
+    // Exécutions comparées, et celles où la recherche exacte a prouvé l'optimum
+    pub samples: usize,
+    pub proven: usize,
+}
+
+#[derive(Serialize)]
+pub struct SizeReport {
+    pub size: usize,
+    pub runs: usize,
+    pub solvers: Vec<SolverTiming>,
+    pub greedy_ratio: Option<GreedyRatio>,
+}
+
+#[derive(Serialize)]
+pub struct BenchReport {
+    pub seed: u64,
+    pub runs: usize,
+    pub topology: Topology,
+    pub max_nodes: u64,
+    pub time_limit_ms: u64,
+    pub sizes: Vec<SizeReport>,
+}
+
+// Rang le plus proche : la plus petite valeur couvrant la fraction `p` des mesures
+fn percentile<T: Copy + Ord>(sorted: &[T], p: f64) -> Option<T> {
+    let rank = (p * sorted.len() as f64).ceil() as usize;
+    sorted.get(rank.max(1) - 1).copied()
+}
+
+struct Samples {
+    algorithm: &'static str,
+    micros: Vec<u64>,
+    nodes: Vec<u64>,
+    solved: usize,
+    optimal: usize,
+}
+
+impl Samples {
+    fn record(&mut self, result: &Option<PathResult>, micros: u64) {
+        self.micros.push(micros);
+        if let Some(result) = result {
+            self.solved += 1;
+            self.optimal += result.optimal as usize;
+            self.nodes.push(result.nodes_expanded);
+        }
+    }
+
+    fn summary(mut self) -> SolverTiming {
+        self.micros.sort_unstable();
+        self.nodes.sort_unstable();
+        let ms = |p| percentile(&self.micros, p).unwrap_or(0) as f64 / 1000.0;
+        SolverTiming {
+            algorithm: self.algorithm,
+            solved: self.solved,
+            optimal: self.optimal,
+            median_ms: ms(0.5),
+            p95_ms: ms(0.95),
+            median_nodes: percentile(&self.nodes, 0.5).unwrap_or(0),
+            p95_nodes: percentile(&self.nodes, 0.95).unwrap_or(0),
+        }
+    }
+}
+
+/// Mesure Dijkstra, le branch-and-bound et le glouton sur `runs` cartes carrées `size`x`size`.
+/// La carte de l'exécution `i` est générée avec la graine `seed + i`, du coin haut-gauche au coin bas-droit.
+pub fn bench_size(size: usize, options: &BenchOptions) -> SizeReport {
+    let movement = options.movement;
+    let solvers: [Box<dyn Solver>; 3] = [
+        Box::new(Dijkstra { movement }),
+        Box::new(MaxPathDfs { movement, budget: options.budget }),
+        Box::new(GreedyMaxPath { movement }),
+    ];
+    let mut samples: Vec<Samples> = solvers.iter()
+        .map(|solver| Samples { algorithm: solver.name(), micros: Vec::new(), nodes: Vec::new(), solved: 0, optimal: 0 })
+        .collect();
+    let mut ratios = Vec::new();
+    let mut proven = 0;
+
+    let (start, end) = ((0, 0), (size - 1, size - 1));
+    for run in 0..options.runs {
+        let seed = options.seed.wrapping_add(run as u64);
+        let grid = generate_map(size, size, options.wall_density, &[start, end], &options.terrain, seed);
+        let results: Vec<Option<PathResult>> = solvers.iter().zip(&mut samples)
+            .map(|(solver, samples)| {
+                let started = Instant::now();
+                let result = solver.solve(&grid, start, end, &mut ());
+                samples.record(&result, started.elapsed().as_micros() as u64);
+                result
+            })
+            .collect();
+
+        if let (Some(exact), Some(greedy)) = (&results[1], &results[2]) {
+            if exact.cost > 0 {
+                ratios.push(greedy.cost as f64 / exact.cost as f64);
+                proven += exact.optimal as usize;
+            }
+        }
+    }
+
+    SizeReport {
+        size,
+        runs: options.runs,
+        solvers: samples.into_iter().map(Samples::summary).collect(),
+        greedy_ratio: greedy_ratio(&ratios, proven),
+    }
+}
+
+fn greedy_ratio(ratios: &[f64], proven: usize) -> Option<GreedyRatio> {
+    (!ratios.is_empty()).then(|| GreedyRatio {
+        mean: ratios.iter().sum::<f64>() / ratios.len() as f64,
+        min: ratios.iter().copied().fold(f64::INFINITY, f64::min),
+        max: ratios.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+        samples: ratios.len(),
+        proven,
+    })
+}
+
+/// En-tête du tableau texte, aligné sur les lignes de `size_lines`
+pub fn header_line() -> String {
+    format!("{:>9}  {:<17} {:>7} {:>11} {:>11} {:>12} {:>12}", "size", "solver", "solved", "median ms", "p95 ms", "median nodes", "p95 nodes")
+}
+
+/// Lignes du tableau texte pour une taille : une par solveur, puis le rapport glouton / exact
+pub fn size_lines(report: &SizeReport) -> Vec<String> {
+    let size = format!("{}x{}", report.size, report.size);
+    let mut lines: Vec<String> = report.solvers.iter()
+        .map(|timing| {
+            let solved = format!("{}/{}", timing.solved, report.runs);
+            format!("{:>9}  {:<17} {:>7} {:>11.3} {:>11.3} {:>12} {:>12}",
+                    size, timing.algorithm, solved, timing.median_ms, timing.p95_ms, timing.median_nodes, timing.p95_nodes)
+        })
+        .collect();
+    lines.push(match &report.greedy_ratio {
+        Some(ratio) => format!("{:>9}  greedy/exact max cost: mean {:.3}, min {:.3}, max {:.3} ({} of {} exact results proven optimal)",
+                               "", ratio.mean, ratio.min, ratio.max, ratio.proven, ratio.samples),
+        None => format!("{:>9}  greedy/exact max cost: no comparable runs", ""),
+    });
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn found(nodes_expanded: u64, optimal: bool) -> Option<PathResult> {
+        Some(PathResult { cost: 1, path: vec![(0, 0)], optimal, nodes_expanded })
+    }
+
+    #[test]
+    fn percentiles_use_the_nearest_rank() {
+        let sorted: Vec<u64> = (1..=20).collect();
+        assert_eq!(percentile(&sorted, 0.5), Some(10));
+        assert_eq!(percentile(&sorted, 0.95), Some(19));
+        assert_eq!(percentile(&sorted, 1.0), Some(20));
+        assert_eq!(percentile(&sorted, 0.0), Some(1));
+        assert_eq!(percentile(&[7u64], 0.95), Some(7));
+        assert_eq!(percentile::<u64>(&[], 0.5), None);
+    }
+
+    #[test]
+    fn samples_count_failures_in_timings_but_not_in_nodes() {
+        let mut samples = Samples { algorithm: "test", micros: Vec::new(), nodes: Vec::new(), solved: 0, optimal: 0 };
+        samples.record(&found(40, true), 3000);
+        samples.record(&None, 9000);
+        samples.record(&found(10, false), 1000);
+        samples.record(&found(20, true), 2000);
+        let timing = samples.summary();
+        assert_eq!((timing.solved, timing.optimal), (3, 2));
+        assert_eq!((timing.median_ms, timing.p95_ms), (2.0, 9.0));
+        assert_eq!((timing.median_nodes, timing.p95_nodes), (20, 40));
+
+        let empty = Samples { algorithm: "test", micros: Vec::new(), nodes: Vec::new(), solved: 0, optimal: 0 }.summary();
+        assert_eq!((empty.median_ms, empty.median_nodes), (0.0, 0));
+    }
+
+    #[test]
+    fn greedy_ratio_summarizes_the_comparisons() {
+        let ratio = greedy_ratio(&[0.5, 1.0, 0.75], 2).unwrap();
+        assert_eq!((ratio.mean, ratio.min, ratio.max), (0.75, 0.5, 1.0));
+        assert_eq!((ratio.samples, ratio.proven), (3, 2));
+        assert!(greedy_ratio(&[], 0).is_none());
+    }
+
+    #[test]
+    fn sizes_report_every_solver_and_run() {
+        let options = BenchOptions {
+            runs: 3,
+            seed: 5,
+            movement: Movement { topology: Topology::Four, diagonal_cost: 1.0 },
+            budget: SearchBudget { max_nodes: 100_000, time_limit: None },
+            wall_density: 0.0,
+            terrain: Terrain::default(),
+        };
+        let report = bench_size(3, &options);
+        assert_eq!((report.size, report.runs), (3, 3));
+        let names: Vec<&str> = report.solvers.iter().map(|timing| timing.algorithm).collect();
+        assert_eq!(names, ["dijkstra", "branch-and-bound", "greedy"]);
+        // Sans mur, chaque solveur trouve un chemin et la recherche exacte termine sur une carte 3x3
+        assert!(report.solvers.iter().all(|timing| timing.solved == 3));
+        assert_eq!(report.solvers[1].optimal, 3);
+        let ratio = report.greedy_ratio.as_ref().unwrap();
+        assert_eq!((ratio.samples, ratio.proven), (3, 3));
+        assert!(0.0 < ratio.min && ratio.min <= ratio.mean && ratio.mean <= ratio.max && ratio.max <= 1.0);
+    }
+
+    #[test]
+    fn text_lines_align_with_the_header() {
+        let timing = |algorithm, solved| SolverTiming {
+            algorithm, solved, optimal: solved, median_ms: 1.5, p95_ms: 12.25, median_nodes: 42, p95_nodes: 1234,
+        };
+        let mut report = SizeReport {
+            size: 16,
+            runs: 4,
+            solvers: vec![timing("dijkstra", 4), timing("greedy", 3)],
+            greedy_ratio: greedy_ratio(&[0.9, 0.8], 1),
+        };
+        let lines = size_lines(&report);
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], "    16x16  dijkstra              4/4       1.500      12.250           42         1234");
+        assert_eq!(lines[1].len(), header_line().len());
+        assert!(lines[1].contains(" greedy ") && lines[1].contains("3/4"));
+        assert_eq!(lines[2], "           greedy/exact max cost: mean 0.850, min 0.800, max 0.900 (1 of 2 exact results proven optimal)");
+
+        report.greedy_ratio = None;
+        assert_eq!(size_lines(&report)[2], "           greedy/exact max cost: no comparable runs");
+    }
+}
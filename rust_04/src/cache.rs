@@ -0,0 +1,169 @@
+use crate::grid::Grid;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+// À changer quand le contenu des entrées change, les anciennes sont alors ignorées
+const CACHE_VERSION: &str = "1";
+
+/// Nombre d'entrées gardées par défaut ; au-delà, les moins récemment utilisées sont supprimées
+pub const MAX_CACHE_ENTRIES: usize = 1000;
+
+/// Résultats déjà calculés, un fichier JSON par entrée.
+/// La date de modification d'une entrée est celle de sa dernière utilisation.
+#[derive(Clone, Debug)]
+pub struct Cache {
+    dir: PathBuf,
+    max_entries: usize,
+}
+
+impl Cache {
+    pub fn new(dir: PathBuf) -> Self {
+        Cache { dir, max_entries: MAX_CACHE_ENTRIES }
+    }
+
+    pub fn with_max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = max_entries;
+        self
+    }
+
+    /// `$XDG_CACHE_HOME/hexpath`, sinon `~/.cache/hexpath` (`%LOCALAPPDATA%\hexpath` sous Windows)
+    pub fn default_dir() -> Option<PathBuf> {
+        let base = env::var_os("XDG_CACHE_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("LOCALAPPDATA").map(PathBuf::from))
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))?;
+        Some(base.join("hexpath"))
+    }
+
+    pub fn dir(&self) -> &PathBuf {
+        &self.dir
+    }
+
+    fn entry(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", key))
+    }
+
+    // Une entrée absente, illisible ou d'un autre format compte comme absente
+    pub fn load<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        let path = self.entry(key);
+        let data = fs::read(&path).ok()?;
+        let value = serde_json::from_slice(&data).ok()?;
+        // Sans droit d'écriture l'entrée vieillit simplement comme si elle n'avait pas servi
+        if let Ok(file) = fs::File::options().write(true).open(&path) {
+            let _ = file.set_modified(SystemTime::now());
+        }
+        Some(value)
+    }
+
+    // Écrit dans un fichier temporaire puis le renomme, pour qu'une lecture concurrente ne voie jamais une entrée partielle
+    pub fn store<T: Serialize>(&self, key: &str, value: &T) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let tmp = self.dir.join(format!("{}.{}.tmp", key, std::process::id()));
+        fs::write(&tmp, serde_json::to_vec(value)?)?;
+        fs::rename(&tmp, self.entry(key))?;
+        self.evict()
+    }
+
+    // Supprime les entrées les moins récemment utilisées au-delà de `max_entries`
+    fn evict(&self) -> io::Result<()> {
+        let mut entries: Vec<(SystemTime, PathBuf)> = fs::read_dir(&self.dir)?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| is_entry(path))
+            .map(|path| (fs::metadata(&path).and_then(|meta| meta.modified()).unwrap_or(SystemTime::UNIX_EPOCH), path))
+            .collect();
+        if entries.len() <= self.max_entries {
+            return Ok(());
+        }
+        entries.sort();
+        let excess = entries.len() - self.max_entries;
+        for (_, path) in entries.into_iter().take(excess) {
+            // Un autre processus a pu la supprimer entre-temps
+            match fs::remove_file(path) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Supprime toutes les entrées et renvoie leur nombre
+    pub fn clear(&self) -> io::Result<usize> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(e),
+        };
+        let mut removed = 0;
+        for entry in entries {
+            let path = entry?.path();
+            if is_entry(&path) || path.extension().is_some_and(|ext| ext == "tmp") {
+                fs::remove_file(path)?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+}
+
+fn is_entry(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "json")
+}
+
+/// Clé d'une entrée : empreinte SHA-256 de la grille lue (dimensions, type de cellule, valeurs et murs)
+/// et des paramètres du solveur, préfixée par `kind` (`min`, `max`...).
+pub fn cache_key(kind: &str, grid: &Grid, params: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(CACHE_VERSION.as_bytes());
+    hasher.update((grid.rows() as u64).to_le_bytes());
+    hasher.update((grid.cols() as u64).to_le_bytes());
+    hasher.update([grid.width().digits() as u8, grid.is_signed() as u8]);
+    for cell in grid.cells() {
+        match cell {
+            Some(value) => {
+                hasher.update([1]);
+                hasher.update(value.to_le_bytes());
+            }
+            None => hasher.update([0]),
+        }
+    }
+    hasher.update(kind.as_bytes());
+    hasher.update([0]);
+    hasher.update(params.as_bytes());
+    let digest = hasher.finalize();
+    let hex: String = digest.iter().map(|b| format!("{:02x}", b)).collect();
+    format!("{}-{}", kind, hex)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn least_recently_used_entries_are_evicted() {
+        let dir = env::temp_dir().join(format!("hexpath-cache-{}", std::process::id()));
+        let cache = Cache::new(dir.clone()).with_max_entries(3);
+        for key in ["a", "b", "c"] {
+            cache.store(key, &key).unwrap();
+            thread::sleep(Duration::from_millis(20));
+        }
+        // `a` sert de nouveau : `b` devient la plus ancienne
+        assert_eq!(cache.load::<String>("a").as_deref(), Some("a"));
+        thread::sleep(Duration::from_millis(20));
+        cache.store("d", &"d").unwrap();
+
+        let kept: Vec<bool> = ["a", "b", "c", "d"].iter().map(|key| cache.load::<String>(key).is_some()).collect();
+        assert_eq!(cache.clear().unwrap(), 3);
+        fs::remove_dir(&dir).unwrap();
+        assert_eq!(kept, [true, false, true, true]);
+    }
+}
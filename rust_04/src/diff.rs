@@ -0,0 +1,262 @@
+use crate::grid::Grid;
+use crate::solver::{path_overlap, Algorithm, Heuristic, MaxPathDfs, PathResult, SearchBudget, Solver};
+use crate::topology::Movement;
+use crate::Coord;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Cellule dont la valeur (ou le mur, `None`) diffère entre les deux versions
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct CellChange {
+    pub pos: Coord,
+    pub old: Option<i64>,
+    pub new: Option<i64>,
+}
+
+/// Portion où les deux chemins se séparent : de `from`, dernière cellule commune, à `to`, où ils se rejoignent.
+/// `old` et `new` ne contiennent que les cellules propres à chaque chemin.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Segment {
+    pub from: Option<Coord>,
+    pub to: Option<Coord>,
+    pub old: Vec<Coord>,
+    pub new: Vec<Coord>,
+    // Coût de chaque version du tronçon, jusqu'à `to` compris, sur sa propre carte
+    pub old_cost: i64,
+    pub new_cost: i64,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct PathComparison {
+    pub old: Option<PathResult>,
+    pub new: Option<PathResult>,
+    pub delta: Option<i64>,
+    // Part des cellules du nouveau chemin déjà empruntées par l'ancien, en pourcentage
+    pub shared: Option<f64>,
+    pub segments: Vec<Segment>,
+}
+
+/// Réglages des recherches faites sur les deux versions
+#[derive(Copy, Clone, Debug)]
+pub struct DiffOptions {
+    pub movement: Movement,
+    pub algorithm: Algorithm,
+    pub heuristic: Heuristic,
+    // Budget de la recherche du chemin maximal, None pour ne comparer que les minimaux
+    pub max_path: Option<SearchBudget>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct MapDiff {
+    pub rows: usize,
+    pub cols: usize,
+    pub start: Coord,
+    pub end: Coord,
+    pub changed: Vec<CellChange>,
+    pub min: PathComparison,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max: Option<PathComparison>,
+}
+
+/// Cellules modifiées, dans l'ordre de lecture ; les deux cartes doivent avoir les mêmes dimensions
+pub fn changed_cells(old: &Grid, new: &Grid) -> Result<Vec<CellChange>, String> {
+    if (old.rows(), old.cols()) != (new.rows(), new.cols()) {
+        return Err(format!("the maps have different sizes: {}x{} and {}x{}", old.rows(), old.cols(), new.rows(), new.cols()));
+    }
+    Ok(old.cells().iter().zip(new.cells()).enumerate()
+        .filter(|(_, (a, b))| a != b)
+        .map(|(i, (&old_cell, &new_cell))| CellChange { pos: old.coord(i), old: old_cell, new: new_cell })
+        .collect())
+}
+
+// Paires d'indices (ancien, nouveau) des cellules communes aux deux chemins, gardées dans le même ordre sur les deux.
+// Un chemin ne repasse jamais par une cellule : la plus longue sous-suite commune est alors la plus longue
+// sous-suite croissante des indices dans le nouveau chemin.
+fn anchors(old: &[Coord], new: &[Coord]) -> Vec<(usize, usize)> {
+    let position: HashMap<Coord, usize> = new.iter().enumerate().map(|(j, &pos)| (pos, j)).collect();
+    let pairs: Vec<(usize, usize)> = old.iter().enumerate()
+        .filter_map(|(i, pos)| position.get(pos).map(|&j| (i, j)))
+        .collect();
+
+    // Tri par paquets : `tails[k]` est la paire qui termine la plus petite sous-suite de longueur k + 1
+    let mut tails: Vec<usize> = Vec::new();
+    let mut previous = vec![None; pairs.len()];
+    for (k, &(_, j)) in pairs.iter().enumerate() {
+        let length = tails.partition_point(|&t| pairs[t].1 < j);
+        previous[k] = length.checked_sub(1).map(|l| tails[l]);
+        if length == tails.len() {
+            tails.push(k);
+        } else {
+            tails[length] = k;
+        }
+    }
+    let mut chain = Vec::new();
+    let mut curr = tails.last().copied();
+    while let Some(k) = curr {
+        chain.push(pairs[k]);
+        curr = previous[k];
+    }
+    chain.reverse();
+    chain
+}
+
+fn cost_between(grid: &Grid, path: &[Coord], movement: Movement) -> i64 {
+    path.windows(2).map(|pair| movement.step_cost(grid, pair[0], pair[1])).sum()
+}
+
+/// Tronçons où `new_path` quitte `old_path`, les coûts étant pris sur la carte de chaque chemin
+pub fn diverging_segments(old: &Grid, old_path: &[Coord], new: &Grid, new_path: &[Coord], movement: Movement) -> Vec<Segment> {
+    let mut bounds: Vec<(Option<usize>, Option<usize>)> = vec![(None, None)];
+    bounds.extend(anchors(old_path, new_path).into_iter().map(|(i, j)| (Some(i), Some(j))));
+    bounds.push((None, None));
+
+    let mut segments = Vec::new();
+    for pair in bounds.windows(2) {
+        let ((a, b), (next_a, next_b)) = (pair[0], pair[1]);
+        // Cellules strictement entre deux ancres, ou jusqu'au bout du chemin avant la première et après la dernière
+        let old_range = a.map_or(0, |a| a + 1)..next_a.unwrap_or(old_path.len());
+        let new_range = b.map_or(0, |b| b + 1)..next_b.unwrap_or(new_path.len());
+        if old_range.is_empty() && new_range.is_empty() {
+            continue;
+        }
+        let from = a.map(|a| old_path[a]);
+        let to = next_a.map(|a| old_path[a]);
+        // Le tronçon complet part de `from` et s'arrête à `to`, pour compter le pas qui rejoint l'autre chemin
+        let stretch = |inner: &[Coord]| -> Vec<Coord> { from.into_iter().chain(inner.iter().copied()).chain(to).collect() };
+        let (old_cells, new_cells) = (&old_path[old_range], &new_path[new_range]);
+        segments.push(Segment {
+            from,
+            to,
+            old: old_cells.to_vec(),
+            new: new_cells.to_vec(),
+            old_cost: cost_between(old, &stretch(old_cells), movement),
+            new_cost: cost_between(new, &stretch(new_cells), movement),
+        });
+    }
+    segments
+}
+
+fn compare(old: &Grid, new: &Grid, old_result: Option<PathResult>, new_result: Option<PathResult>, movement: Movement) -> PathComparison {
+    let (delta, shared, segments) = match (&old_result, &new_result) {
+        (Some(a), Some(b)) => (
+            Some(b.cost - a.cost),
+            Some(path_overlap(&b.path, &a.path)),
+            diverging_segments(old, &a.path, new, &b.path, movement),
+        ),
+        _ => (None, None, Vec::new()),
+    };
+    PathComparison { old: old_result, new: new_result, delta, shared, segments }
+}
+
+fn solve_min(grid: &Grid, version: &str, start: Coord, end: Coord, options: &DiffOptions) -> Result<Option<PathResult>, String> {
+    if grid.is_wall(start) || grid.is_wall(end) {
+        return Ok(None);
+    }
+    let solver = options.algorithm.for_grid(grid, options.movement, options.heuristic).map_err(|e| format!("{} map: {}", version, e))?;
+    solver.try_solve(grid, start, end, &mut ()).map_err(|cycle| format!("No minimum cost path on the {} map: {}", version, cycle))
+}
+
+/// Compare deux versions d'une carte : cellules modifiées, puis chemins minimaux (et maximaux si `options.max_path`
+/// est donné) résolus sur chacune, avec l'écart de coût et les tronçons qui ont changé.
+pub fn diff_maps(old: &Grid, new: &Grid, start: Coord, end: Coord, options: &DiffOptions) -> Result<MapDiff, String> {
+    let changed = changed_cells(old, new)?;
+    for (r, c) in [start, end] {
+        if !old.contains((r, c)) {
+            return Err(format!("Coordinate ({},{}) is outside the {}x{} grid", r, c, old.rows(), old.cols()));
+        }
+    }
+
+    let movement = options.movement;
+    let (old_min, new_min) = (solve_min(old, "old", start, end, options)?, solve_min(new, "new", start, end, options)?);
+    let min = compare(old, new, old_min, new_min, movement);
+    let max = options.max_path.map(|budget| {
+        let solver = MaxPathDfs { movement, budget };
+        let solve = |grid: &Grid| (!grid.is_wall(start) && !grid.is_wall(end)).then(|| solver.solve(grid, start, end, &mut ())).flatten();
+        compare(old, new, solve(old), solve(new), movement)
+    });
+    Ok(MapDiff { rows: old.rows(), cols: old.cols(), start, end, changed, min, max })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::testing::MOVEMENTS;
+
+    const FOUR: Movement = MOVEMENTS[0];
+
+    fn grid(values: &[i64], cols: usize) -> Grid {
+        Grid::new(values.len() / cols, cols, values.iter().map(|&v| (v >= 0).then_some(v)).collect())
+    }
+
+    fn options() -> DiffOptions {
+        DiffOptions { movement: FOUR, algorithm: Algorithm::Dijkstra, heuristic: Heuristic::Manhattan, max_path: None }
+    }
+
+    #[test]
+    fn identical_paths_have_no_segment() {
+        let grid = grid(&[1, 2, 3, 4, 5, 6], 3);
+        let path = [(0, 0), (0, 1), (0, 2), (1, 2)];
+        assert_eq!(anchors(&path, &path), [(0, 0), (1, 1), (2, 2), (3, 3)]);
+        assert!(diverging_segments(&grid, &path, &grid, &path, FOUR).is_empty());
+
+        let diff = diff_maps(&grid, &grid, (0, 0), (1, 2), &options()).unwrap();
+        assert!(diff.changed.is_empty());
+        assert_eq!((diff.min.delta, diff.min.shared), (Some(0), Some(100.0)));
+        assert!(diff.min.segments.is_empty() && diff.max.is_none());
+    }
+
+    #[test]
+    fn disjoint_paths_form_a_single_segment() {
+        let grid = grid(&[1, 2, 3, 4, 5, 6, 7, 8, 9], 3);
+        let old = [(0, 1), (0, 2), (1, 2)];
+        let new = [(1, 0), (2, 0), (2, 1)];
+        assert!(anchors(&old, &new).is_empty());
+        let segments = diverging_segments(&grid, &old, &grid, &new, FOUR);
+        assert_eq!(segments, [Segment { from: None, to: None, old: old.to_vec(), new: new.to_vec(), old_cost: 3 + 6, new_cost: 7 + 8 }]);
+    }
+
+    #[test]
+    fn divergent_middle_is_bounded_by_the_shared_prefix_and_suffix() {
+        // Le mur posé en (0,2) fait passer le nouveau chemin par la deuxième ligne
+        let old_grid = grid(&[1, 1, 1, 1, 1, 9, 9, 9, 9, 9], 5);
+        let mut new_grid = old_grid.clone();
+        new_grid.set((0, 2), None);
+        let diff = diff_maps(&old_grid, &new_grid, (0, 0), (0, 4), &options()).unwrap();
+        assert_eq!(diff.changed, [CellChange { pos: (0, 2), old: Some(1), new: None }]);
+
+        let (old, new) = (diff.min.old.as_ref().unwrap(), diff.min.new.as_ref().unwrap());
+        assert_eq!(old.path, [(0, 0), (0, 1), (0, 2), (0, 3), (0, 4)]);
+        assert_eq!(new.path, [(0, 0), (0, 1), (1, 1), (1, 2), (1, 3), (0, 3), (0, 4)]);
+        assert_eq!((diff.min.delta, diff.min.shared), (Some(30 - 4), Some(4.0 * 100.0 / 7.0)));
+        assert_eq!(diff.min.segments, [Segment {
+            from: Some((0, 1)),
+            to: Some((0, 3)),
+            old: vec![(0, 2)],
+            new: vec![(1, 1), (1, 2), (1, 3)],
+            old_cost: 2,
+            new_cost: 9 * 3 + 1,
+        }]);
+    }
+
+    #[test]
+    fn crossing_orders_keep_the_longest_common_subsequence() {
+        // (0,1) et (1,0) sont communs mais dans l'ordre inverse : un seul peut servir d'ancre
+        let old = [(0, 0), (0, 1), (1, 1), (1, 0), (2, 0)];
+        let new = [(0, 0), (1, 0), (1, 1), (0, 1), (0, 2), (2, 0)];
+        let chain = anchors(&old, &new);
+        assert_eq!(chain.len(), 3);
+        assert_eq!((chain[0], chain[2]), ((0, 0), (4, 5)));
+        assert!(chain.windows(2).all(|pair| pair[0].0 < pair[1].0 && pair[0].1 < pair[1].1));
+        for (i, j) in chain {
+            assert_eq!(old[i], new[j]);
+        }
+    }
+
+    #[test]
+    fn maps_of_different_sizes_are_rejected() {
+        let error = diff_maps(&grid(&[1, 2, 3, 4], 2), &grid(&[1, 2, 3, 4], 4), (0, 0), (0, 1), &options()).unwrap_err();
+        assert_eq!(error, "the maps have different sizes: 2x2 and 1x4");
+        let error = diff_maps(&grid(&[1, 2, 3, 4], 2), &grid(&[1, 2, 3, 4], 2), (0, 0), (2, 1), &options()).unwrap_err();
+        assert_eq!(error, "Coordinate (2,1) is outside the 2x2 grid");
+    }
+}
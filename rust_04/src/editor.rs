@@ -0,0 +1,532 @@
+use crate::grid::Grid;
+use crate::render::{cell_ansi, cell_label, hex_cost};
+use crate::solver::{Algorithm, Heuristic, MaxPathDfs, PathResult, SearchBudget, Solver};
+use crate::topology::{Movement, Topology};
+use crate::Coord;
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::{cursor, execute, queue, terminal};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+const CURSOR_ANSI: &str = "\x1b[7m";
+const START_ANSI: &str = "\x1b[30;42m";
+const END_ANSI: &str = "\x1b[30;46m";
+const MIN_PATH_ANSI: &str = "\x1b[1;37m";
+const MAX_PATH_ANSI: &str = "\x1b[1;31m";
+const BOTH_PATHS_ANSI: &str = "\x1b[1;35m";
+
+const HELP: &str = "arrows/hjkl move  0-9A-F type value  +/- adjust  space wall  s start  t end  \
+                    m min  M max  w save  q quit";
+
+// Commande de l'éditeur, indépendante du terminal
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Action {
+    Move(isize, isize),
+    Digit(char),
+    CommitInput,
+    EraseDigit,
+    ClearInput,
+    Adjust(i64),
+    ToggleWall,
+    SetStart,
+    SetEnd,
+    ToggleMin,
+    ToggleMax,
+    Save,
+    Quit,
+    ForceQuit,
+}
+
+// Touche correspondant à chaque commande, None pour les touches ignorées et les relâchements
+fn key_action(key: KeyEvent) -> Option<Action> {
+    if key.kind == KeyEventKind::Release {
+        return None;
+    }
+    let action = match key.code {
+        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => Action::ForceQuit,
+        KeyCode::Char(ch) if ch.is_ascii_hexdigit() => Action::Digit(ch.to_ascii_uppercase()),
+        KeyCode::Enter => Action::CommitInput,
+        KeyCode::Backspace => Action::EraseDigit,
+        KeyCode::Esc => Action::ClearInput,
+        KeyCode::Up | KeyCode::Char('k') => Action::Move(-1, 0),
+        KeyCode::Down | KeyCode::Char('j') => Action::Move(1, 0),
+        KeyCode::Left | KeyCode::Char('h') => Action::Move(0, -1),
+        KeyCode::Right | KeyCode::Char('l') => Action::Move(0, 1),
+        KeyCode::Char('+') | KeyCode::Char('=') => Action::Adjust(1),
+        KeyCode::Char('-') => Action::Adjust(-1),
+        KeyCode::Char(' ') => Action::ToggleWall,
+        KeyCode::Char('s') => Action::SetStart,
+        KeyCode::Char('t') => Action::SetEnd,
+        KeyCode::Char('m') => Action::ToggleMin,
+        KeyCode::Char('M') => Action::ToggleMax,
+        KeyCode::Char('w') => Action::Save,
+        KeyCode::Char('q') => Action::Quit,
+        _ => return None,
+    };
+    Some(action)
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct EditorOptions {
+    pub movement: Movement,
+    pub budget: SearchBudget,
+    pub start: Coord,
+    pub end: Coord,
+}
+
+// Remet le terminal en état même si l'éditeur s'arrête sur une erreur
+struct TerminalGuard;
+
+impl TerminalGuard {
+    fn enter() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        execute!(io::stdout(), terminal::EnterAlternateScreen, cursor::Hide)?;
+        Ok(TerminalGuard)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), cursor::Show, terminal::LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+struct Editor {
+    grid: Grid,
+    save_path: PathBuf,
+    options: EditorOptions,
+    cursor: Coord,
+    scroll: Coord,
+    // Valeur saisie chiffre par chiffre, appliquée quand elle a la largeur d'une cellule
+    input: String,
+    // Valeurs des cellules transformées en murs, pour pouvoir les rétablir
+    removed: HashMap<Coord, i64>,
+    show_min: bool,
+    show_max: bool,
+    min: Option<PathResult>,
+    max: Option<PathResult>,
+    message: String,
+    dirty: bool,
+    quit_armed: bool,
+}
+
+impl Editor {
+    fn new(grid: Grid, save_path: PathBuf, options: EditorOptions) -> Self {
+        let mut editor = Editor {
+            grid,
+            save_path,
+            options,
+            cursor: options.start,
+            scroll: (0, 0),
+            input: String::new(),
+            removed: HashMap::new(),
+            show_min: true,
+            show_max: false,
+            min: None,
+            max: None,
+            message: String::new(),
+            dirty: false,
+            quit_armed: false,
+        };
+        editor.solve();
+        editor
+    }
+
+    fn solve(&mut self) {
+        let EditorOptions { movement, start, end, .. } = self.options;
+        self.min = None;
+        self.max = None;
+        if self.grid.is_wall(start) || self.grid.is_wall(end) {
+            self.message = "start or end is a wall".to_string();
+            return;
+        }
+
+        if self.show_min {
+            let solver = Algorithm::Dijkstra.for_grid(&self.grid, movement, Heuristic::Zero);
+            match solver.and_then(|solver| solver.try_solve(&self.grid, start, end, &mut ()).map_err(|cycle| cycle.to_string())) {
+                Ok(min) => self.min = min,
+                Err(e) => self.message = e,
+            }
+        }
+        if self.show_max {
+            self.max = MaxPathDfs { movement, budget: self.options.budget }.solve(&self.grid, start, end, &mut ());
+        }
+    }
+
+    fn edited(&mut self) {
+        self.dirty = true;
+        self.solve();
+    }
+
+    fn value_range(&self) -> (i64, i64) {
+        let max = self.grid.width().max_raw() as i64;
+        if self.grid.is_signed() {
+            (-(max / 2) - 1, max / 2)
+        } else {
+            (0, max)
+        }
+    }
+
+    fn set_value(&mut self, value: i64) {
+        let (low, high) = self.value_range();
+        self.removed.remove(&self.cursor);
+        self.grid.set(self.cursor, Some(value.clamp(low, high)));
+        self.edited();
+    }
+
+    fn commit_input(&mut self) {
+        if self.input.is_empty() {
+            return;
+        }
+        let raw = u32::from_str_radix(&self.input, 16).unwrap_or(0);
+        self.input.clear();
+        let width = self.grid.width();
+        let value = if self.grid.is_signed() { width.to_signed(raw) } else { raw as i64 };
+        self.set_value(value);
+    }
+
+    fn toggle_wall(&mut self) {
+        let pos = self.cursor;
+        match self.grid.cell(pos) {
+            Some(value) => {
+                self.removed.insert(pos, value);
+                self.grid.set(pos, None);
+            }
+            None => {
+                let value = self.removed.remove(&pos).unwrap_or(0);
+                self.grid.set(pos, Some(value));
+            }
+        }
+        self.edited();
+    }
+
+    fn move_cursor(&mut self, dr: isize, dc: isize) {
+        self.input.clear();
+        let r = self.cursor.0.saturating_add_signed(dr).min(self.grid.rows() - 1);
+        let c = self.cursor.1.saturating_add_signed(dc).min(self.grid.cols() - 1);
+        self.cursor = (r, c);
+    }
+
+    fn save(&mut self) -> io::Result<()> {
+        fs::write(&self.save_path, self.grid.to_string())?;
+        self.dirty = false;
+        self.message = format!("saved to {}", self.save_path.display());
+        Ok(())
+    }
+
+    // Renvoie false quand il faut quitter
+    fn apply(&mut self, action: Action) -> io::Result<bool> {
+        let quit_armed = std::mem::take(&mut self.quit_armed);
+        self.message.clear();
+
+        match action {
+            Action::ForceQuit => return Ok(false),
+            Action::Digit(ch) => {
+                self.input.push(ch);
+                if self.input.len() == self.grid.width().digits() {
+                    self.commit_input();
+                }
+            }
+            Action::CommitInput => self.commit_input(),
+            Action::EraseDigit => {
+                self.input.pop();
+            }
+            Action::ClearInput => self.input.clear(),
+            Action::Move(dr, dc) => self.move_cursor(dr, dc),
+            Action::Adjust(delta) => {
+                if let Some(value) = self.grid.cell(self.cursor) {
+                    self.set_value(value + delta);
+                }
+            }
+            Action::ToggleWall => self.toggle_wall(),
+            Action::SetStart => {
+                self.options.start = self.cursor;
+                self.solve();
+            }
+            Action::SetEnd => {
+                self.options.end = self.cursor;
+                self.solve();
+            }
+            Action::ToggleMin => {
+                self.show_min = !self.show_min;
+                self.solve();
+            }
+            Action::ToggleMax => {
+                self.show_max = !self.show_max;
+                self.solve();
+            }
+            Action::Save => self.save()?,
+            Action::Quit => {
+                if !self.dirty || quit_armed {
+                    return Ok(false);
+                }
+                self.quit_armed = true;
+                self.message = "unsaved changes, press q again to quit without saving".to_string();
+            }
+        }
+        Ok(true)
+    }
+
+    // Fait défiler la vue pour garder le curseur visible
+    fn visible_area(&mut self, width: usize, height: usize) -> (usize, usize) {
+        let cell_width = self.grid.width().digits() + 1;
+        let rows = height.saturating_sub(5).max(1);
+        let cols = (width.saturating_sub(2) / cell_width).max(1);
+        let (r, c) = self.cursor;
+        if r < self.scroll.0 {
+            self.scroll.0 = r;
+        } else if r >= self.scroll.0 + rows {
+            self.scroll.0 = r + 1 - rows;
+        }
+        if c < self.scroll.1 {
+            self.scroll.1 = c;
+        } else if c >= self.scroll.1 + cols {
+            self.scroll.1 = c + 1 - cols;
+        }
+        (rows, cols)
+    }
+
+    // Dessine la vue dans un terminal de `width` x `height` caractères
+    fn draw(&mut self, out: &mut impl Write, width: usize, height: usize) -> io::Result<()> {
+        let (rows, cols) = self.visible_area(width, height);
+        let on_path = |result: &Option<PathResult>| -> Vec<Coord> {
+            result.as_ref().map(|r| r.path.clone()).unwrap_or_default()
+        };
+        let mut overlay: HashMap<Coord, &str> = HashMap::new();
+        for pos in on_path(&self.max) {
+            overlay.insert(pos, MAX_PATH_ANSI);
+        }
+        for pos in on_path(&self.min) {
+            let color = if overlay.contains_key(&pos) { BOTH_PATHS_ANSI } else { MIN_PATH_ANSI };
+            overlay.insert(pos, color);
+        }
+
+        queue!(out, cursor::MoveTo(0, 0), terminal::Clear(terminal::ClearType::All))?;
+        let last_row = (self.scroll.0 + rows).min(self.grid.rows());
+        let last_col = (self.scroll.1 + cols).min(self.grid.cols());
+        for r in self.scroll.0..last_row {
+            if self.options.movement.topology == Topology::Hex && r % 2 == 1 {
+                write!(out, " ")?;
+            }
+            for c in self.scroll.1..last_col {
+                let pos = (r, c);
+                let mut style = cell_ansi(self.grid.intensity(pos));
+                if let Some(color) = overlay.get(&pos) {
+                    style.push_str(color);
+                }
+                if pos == self.options.start {
+                    style.push_str(START_ANSI);
+                } else if pos == self.options.end {
+                    style.push_str(END_ANSI);
+                }
+                if pos == self.cursor {
+                    style.push_str(CURSOR_ANSI);
+                }
+                let token = if pos == self.cursor && !self.input.is_empty() {
+                    format!("{:_<width$}", self.input, width = self.grid.width().digits())
+                } else {
+                    self.grid.token(pos)
+                };
+                write!(out, "{}{}\x1b[0m ", style, token)?;
+            }
+            write!(out, "\r\n")?;
+        }
+
+        let (r, c) = self.cursor;
+        let describe = |name: &str, result: &Option<PathResult>, enabled: bool| match (enabled, result) {
+            (false, _) => format!("{} off", name),
+            (true, None) => format!("{} none", name),
+            (true, Some(result)) => format!("{} {} ({}){}", name, hex_cost(result.cost), result.cost,
+                                            if result.optimal { "" } else { " best found" }),
+        };
+        write!(out, "\r\n({},{}) {}   {}   {}{}\r\n", r, c, cell_label(&self.grid, self.cursor),
+               describe("min", &self.min, self.show_min), describe("max", &self.max, self.show_max),
+               if self.dirty { "   [modified]" } else { "" })?;
+        write!(out, "{}\r\n", self.message)?;
+        write!(out, "\x1b[2m{}\x1b[0m", HELP)?;
+        out.flush()
+    }
+}
+
+/// Éditeur plein écran : modification des cellules, départ/arrivée et chemins recalculés à chaque changement.
+/// L'enregistrement se fait toujours au format texte dans `save_path`.
+pub fn run_editor(grid: Grid, save_path: PathBuf, options: EditorOptions) -> io::Result<()> {
+    let mut editor = Editor::new(grid, save_path, options);
+
+    let _guard = TerminalGuard::enter()?;
+    let mut out = io::stdout();
+    loop {
+        let (width, height) = terminal::size()?;
+        editor.draw(&mut out, width as usize, height as usize)?;
+        if let Event::Key(key) = event::read()? {
+            if let Some(action) = key_action(key) {
+                if !editor.apply(action)? {
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::{parse_map, CellWidth, ParseOptions};
+    use crate::topology::Topology;
+
+    fn editor(text: &str) -> Editor {
+        with_grid(parse_map(text, ParseOptions::default()).unwrap())
+    }
+
+    fn with_grid(grid: Grid) -> Editor {
+        let end = (grid.rows() - 1, grid.cols() - 1);
+        let options = EditorOptions {
+            movement: Movement { topology: Topology::Four, diagonal_cost: 1.0 },
+            budget: SearchBudget { max_nodes: 10_000, time_limit: None },
+            start: (0, 0),
+            end,
+        };
+        Editor::new(grid, std::env::temp_dir().join(format!("hexpath-editor-{}.txt", std::process::id())), options)
+    }
+
+    fn apply_all(editor: &mut Editor, actions: &[Action]) -> bool {
+        actions.iter().all(|&action| editor.apply(action).unwrap())
+    }
+
+    fn min_cost(editor: &Editor) -> Option<i64> {
+        editor.min.as_ref().map(|min| min.cost)
+    }
+
+    #[test]
+    fn keys_map_to_actions() {
+        let press = |code| key_action(KeyEvent::new(code, KeyModifiers::NONE));
+        assert_eq!(press(KeyCode::Char('k')), Some(Action::Move(-1, 0)));
+        assert_eq!(press(KeyCode::Right), Some(Action::Move(0, 1)));
+        assert_eq!(press(KeyCode::Char('b')), Some(Action::Digit('B')));
+        assert_eq!(press(KeyCode::Char('=')), Some(Action::Adjust(1)));
+        assert_eq!(press(KeyCode::Char('M')), Some(Action::ToggleMax));
+        assert_eq!(press(KeyCode::Char('z')), None);
+        assert_eq!(key_action(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)), Some(Action::ForceQuit));
+        let mut release = KeyEvent::new(KeyCode::Char('q'), KeyModifiers::NONE);
+        release.kind = KeyEventKind::Release;
+        assert_eq!(key_action(release), None);
+    }
+
+    #[test]
+    fn typed_digits_are_applied_at_the_cell_width() {
+        let mut editor = editor("01 01 01\n09 09 01\n");
+        assert_eq!(min_cost(&editor), Some(3));
+        apply_all(&mut editor, &[Action::Move(0, 1), Action::Digit('2')]);
+        assert_eq!((editor.input.as_str(), editor.grid.cell((0, 1)), editor.dirty), ("2", Some(1), false));
+        apply_all(&mut editor, &[Action::Digit('A')]);
+        assert_eq!((editor.input.as_str(), editor.grid.cell((0, 1)), editor.dirty), ("", Some(0x2A), true));
+        // Le chemin minimal est recalculé après chaque modification
+        assert_eq!(min_cost(&editor), Some(9 + 9 + 1));
+
+        // Une saisie partielle s'applique avec Entrée et se perd en changeant de cellule
+        apply_all(&mut editor, &[Action::Digit('5'), Action::CommitInput]);
+        assert_eq!(editor.grid.cell((0, 1)), Some(5));
+        apply_all(&mut editor, &[Action::Digit('7'), Action::EraseDigit, Action::Digit('3'), Action::Move(0, 1)]);
+        assert_eq!((editor.grid.cell((0, 1)), editor.input.as_str(), editor.cursor), (Some(5), "", (0, 2)));
+    }
+
+    #[test]
+    fn adjustments_stay_within_the_cell_range() {
+        let mut editor = editor("FE 00\n01 01\n");
+        apply_all(&mut editor, &[Action::Adjust(1), Action::Adjust(1)]);
+        assert_eq!(editor.grid.cell((0, 0)), Some(0xFF));
+        apply_all(&mut editor, &[Action::Move(0, 1), Action::Adjust(-1)]);
+        assert_eq!(editor.grid.cell((0, 1)), Some(0));
+
+        let grid = Grid::new(1, 2, vec![Some(127), Some(-128)]).with_cell_type(CellWidth::U8, true);
+        let mut editor = with_grid(grid);
+        apply_all(&mut editor, &[Action::Adjust(1), Action::Move(0, 1), Action::Adjust(-1)]);
+        assert_eq!(editor.grid.cells(), [Some(127), Some(-128)]);
+        apply_all(&mut editor, &[Action::Digit('F'), Action::Digit('F')]);
+        assert_eq!(editor.grid.cell((0, 1)), Some(-1));
+    }
+
+    #[test]
+    fn walls_remember_the_value_they_replaced() {
+        let mut editor = editor("01 07 01\n05 05 05\n");
+        apply_all(&mut editor, &[Action::Move(0, 1), Action::ToggleWall]);
+        assert!(editor.grid.is_wall((0, 1)));
+        assert_eq!(min_cost(&editor), Some(5 + 5 + 5));
+        apply_all(&mut editor, &[Action::ToggleWall]);
+        assert_eq!(editor.grid.cell((0, 1)), Some(7));
+        assert_eq!(min_cost(&editor), Some(7 + 1 + 5));
+
+        // Une valeur tapée sur un mur le remplace
+        apply_all(&mut editor, &[Action::ToggleWall, Action::Digit('0'), Action::Digit('3'), Action::ToggleWall, Action::ToggleWall]);
+        assert_eq!(editor.grid.cell((0, 1)), Some(3));
+    }
+
+    #[test]
+    fn endpoints_and_displayed_paths_follow_the_cursor() {
+        let mut editor = editor("01 02 03\n04 05 06\n");
+        apply_all(&mut editor, &[Action::Move(1, 0), Action::SetStart, Action::Move(-1, 2), Action::SetEnd]);
+        assert_eq!((editor.options.start, editor.options.end), ((1, 0), (0, 2)));
+        assert_eq!(min_cost(&editor), Some(1 + 2 + 3));
+
+        apply_all(&mut editor, &[Action::ToggleMax]);
+        assert_eq!(editor.max.as_ref().map(|max| max.cost), Some(1 + 2 + 5 + 6 + 3));
+        apply_all(&mut editor, &[Action::ToggleMin]);
+        assert!(editor.min.is_none() && editor.max.is_some());
+
+        apply_all(&mut editor, &[Action::ToggleMin, Action::Move(0, 0), Action::ToggleWall]);
+        assert_eq!((editor.min.as_ref(), editor.message.as_str()), (None, "start or end is a wall"));
+        // Le curseur reste dans la grille
+        apply_all(&mut editor, &[Action::Move(-5, 9)]);
+        assert_eq!(editor.cursor, (0, 2));
+    }
+
+    #[test]
+    fn quitting_with_unsaved_changes_asks_twice() {
+        let mut editor = editor("01 02\n03 04\n");
+        assert!(!editor.apply(Action::Quit).unwrap());
+
+        let mut editor = self::editor("01 02\n03 04\n");
+        apply_all(&mut editor, &[Action::Adjust(1)]);
+        assert!(editor.apply(Action::Quit).unwrap());
+        assert!(editor.message.starts_with("unsaved changes"));
+        // Une autre touche annule la confirmation
+        assert!(apply_all(&mut editor, &[Action::Move(0, 1), Action::Quit]));
+        assert!(!editor.apply(Action::Quit).unwrap());
+        assert!(!editor.apply(Action::ForceQuit).unwrap());
+    }
+
+    #[test]
+    fn saved_maps_are_read_back() {
+        let mut editor = editor("01 02\n03 04\n");
+        apply_all(&mut editor, &[Action::Move(1, 1), Action::ToggleWall, Action::Move(-1, 0), Action::Digit('E'), Action::Digit('E')]);
+        assert!(apply_all(&mut editor, &[Action::Save]));
+        let saved = fs::read_to_string(&editor.save_path).unwrap();
+        fs::remove_file(&editor.save_path).unwrap();
+        assert!(!editor.dirty && editor.message.starts_with("saved to "));
+        let grid = parse_map(&saved, ParseOptions::default()).unwrap();
+        assert_eq!(grid.cells(), [Some(1), Some(0xEE), Some(3), None]);
+        assert!(!editor.apply(Action::Quit).unwrap());
+    }
+
+    #[test]
+    fn view_scrolls_to_keep_the_cursor_visible() {
+        let mut editor = editor(&"01 02 03 04 05 06 07 08\n".repeat(20));
+        let mut screen = Vec::new();
+        editor.draw(&mut screen, 14, 10).unwrap();
+        let screen = String::from_utf8(screen).unwrap();
+        // 10 lignes de terminal : 5 lignes de grille, 14 colonnes : 4 cellules
+        assert_eq!(screen.matches("\r\n").count(), 5 + 3);
+        assert!(screen.contains("min 0x") && screen.contains("max off"));
+        assert_eq!(editor.visible_area(14, 10), (5, 4));
+
+        apply_all(&mut editor, &[Action::Move(7, 6)]);
+        assert_eq!(editor.visible_area(14, 10), (5, 4));
+        assert_eq!(editor.scroll, (3, 3));
+        apply_all(&mut editor, &[Action::Move(-7, -6)]);
+        editor.visible_area(14, 10);
+        assert_eq!(editor.scroll, (0, 0));
+    }
+}
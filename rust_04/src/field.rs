@@ -0,0 +1,225 @@
+use crate::grid::{CellWidth, Grid};
+use crate::topology::{Movement, Topology};
+use crate::Coord;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+/// Codes de direction écrits dans la grille des directions : 0 pour la source, puis la rose des vents
+/// dans le sens horaire. En hexagonal, seuls NE, E, SE, SW, W et NW existent.
+pub const DIRECTION_NAMES: [&str; 9] = ["source", "N", "NE", "E", "SE", "S", "SW", "W", "NW"];
+
+/// Distances depuis une source vers toutes les cellules atteignables
+#[derive(Clone, Debug)]
+pub struct DistanceField {
+    rows: usize,
+    cols: usize,
+    pub source: Coord,
+    // true si les distances sont les coûts pour rejoindre la source, et non pour en venir
+    pub reverse: bool,
+    pub distances: Vec<Option<i64>>,
+    // Cellule suivante vers la source sur un chemin optimal, None pour la source et les cellules inatteignables
+    pub toward: Vec<Option<Coord>>,
+}
+
+/// Dijkstra mené jusqu'au bout depuis `source`, coûts positifs uniquement.
+/// Sans `reverse`, la distance d'une cellule est le coût du meilleur chemin de `source` jusqu'à elle ;
+/// avec `reverse`, c'est le coût du meilleur chemin d'elle jusqu'à `source` (champ de flux vers une cible).
+pub fn distance_field(grid: &Grid, source: Coord, movement: Movement, reverse: bool) -> DistanceField {
+    let n = grid.len();
+    let mut distances = vec![None; n];
+    let mut toward = vec![None; n];
+    let mut done = vec![false; n];
+    let mut heap = BinaryHeap::new();
+
+    if !grid.is_wall(source) {
+        distances[grid.index(source)] = Some(0);
+        heap.push(Reverse((0i64, grid.index(source))));
+    }
+    while let Some(Reverse((cost, index))) = heap.pop() {
+        if done[index] {
+            continue;
+        }
+        done[index] = true;
+        let position = grid.coord(index);
+        for next in movement.neighbors(position, grid) {
+            // En sens inverse on remonte l'arête next -> position, qui coûte l'entrée dans `position`
+            let step = if reverse { movement.step_cost(grid, next, position) } else { movement.step_cost(grid, position, next) };
+            let Some(total) = cost.checked_add(step) else { continue };
+            let i = grid.index(next);
+            if distances[i].is_none_or(|d| total < d) {
+                distances[i] = Some(total);
+                toward[i] = Some(position);
+                heap.push(Reverse((total, i)));
+            }
+        }
+    }
+
+    DistanceField { rows: grid.rows(), cols: grid.cols(), source, reverse, distances, toward }
+}
+
+/// Code de direction (voir `DIRECTION_NAMES`) du pas de `from` vers la cellule voisine `to`
+pub fn direction_code(from: Coord, to: Coord, topology: Topology) -> u8 {
+    let dr = to.0 as isize - from.0 as isize;
+    let dc = to.1 as isize - from.1 as isize;
+    if topology == Topology::Hex && dr != 0 {
+        // Lignes impaires décalées à droite : sur une ligne paire, le voisin de droite est dans la même colonne
+        let right = dc == if from.0.is_multiple_of(2) { 0 } else { 1 };
+        return match (dr < 0, right) {
+            (true, true) => 2,
+            (false, true) => 4,
+            (false, false) => 6,
+            (true, false) => 8,
+        };
+    }
+    match (dr, dc) {
+        (0, 0) => 0,
+        (-1, 0) => 1,
+        (-1, 1) => 2,
+        (0, 1) => 3,
+        (1, 1) => 4,
+        (1, 0) => 5,
+        (1, -1) => 6,
+        (0, -1) => 7,
+        // (-1, -1)
+        _ => 8,
+    }
+}
+
+impl DistanceField {
+    pub fn reached(&self) -> usize {
+        self.distances.iter().flatten().count()
+    }
+
+    pub fn max_distance(&self) -> Option<i64> {
+        self.distances.iter().flatten().copied().max()
+    }
+
+    /// Distances en grille, sur la plus petite largeur de cellule qui les contient ; murs et cellules inatteignables sont des murs
+    pub fn distance_grid(&self) -> Result<Grid, String> {
+        let max = self.max_distance().unwrap_or(0);
+        let width = [CellWidth::U8, CellWidth::U16, CellWidth::U32].into_iter()
+            .find(|width| max <= width.max_raw() as i64)
+            .ok_or_else(|| format!("distance {} does not fit in 8 hex digits", max))?;
+        Ok(Grid::new(self.rows, self.cols, self.distances.clone()).with_cell_type(width, false))
+    }
+
+    /// Directions vers la source, en codes de `DIRECTION_NAMES`
+    pub fn direction_grid(&self, topology: Topology) -> Grid {
+        let cells = (0..self.distances.len())
+            .map(|i| {
+                let pos = (i / self.cols, i % self.cols);
+                match (self.distances[i], self.toward[i]) {
+                    (Some(_), Some(next)) => Some(direction_code(pos, next, topology) as i64),
+                    (Some(_), None) => Some(0),
+                    (None, _) => None,
+                }
+            })
+            .collect();
+        Grid::new(self.rows, self.cols, cells)
+    }
+
+    /// Distances ramenées sur 00-FF pour l'affichage en dégradé
+    pub fn heatmap(&self) -> Grid {
+        let max = self.max_distance().unwrap_or(0).max(1);
+        let cells = self.distances.iter().map(|d| d.map(|d| d * 255 / max)).collect();
+        Grid::new(self.rows, self.cols, cells)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::dijkstra;
+    use crate::solver::testing::{tiny_grid, MOVEMENTS};
+
+    // Remonte les codes de direction depuis `from` jusqu'au code 0, en renvoyant les cellules parcourues
+    fn follow(directions: &Grid, grid: &Grid, from: Coord, movement: Movement) -> Vec<Coord> {
+        let mut walk = vec![from];
+        let mut pos = from;
+        while let Some(code) = directions.cell(pos).filter(|&code| code != 0) {
+            pos = movement.neighbors(pos, grid)
+                .find(|&next| direction_code(pos, next, movement.topology) as i64 == code)
+                .unwrap_or_else(|| panic!("no neighbour of {:?} in direction {}", pos, code));
+            walk.push(pos);
+            assert!(walk.len() <= grid.len(), "directions loop from {:?}", from);
+        }
+        walk
+    }
+
+    #[test]
+    fn distances_match_dijkstra_in_both_directions() {
+        for movement in MOVEMENTS {
+            for seed in 0..8 {
+                let grid = tiny_grid(5, 6, 0.2, false, seed);
+                let source = (2, 3);
+                if grid.is_wall(source) {
+                    continue;
+                }
+                for reverse in [false, true] {
+                    let field = distance_field(&grid, source, movement, reverse);
+                    for i in 0..grid.len() {
+                        let pos = grid.coord(i);
+                        let (from, to) = if reverse { (pos, source) } else { (source, pos) };
+                        let expected = if grid.is_wall(pos) { None } else { dijkstra(&grid, from, to, movement, &mut ()).map(|(cost, _)| cost) };
+                        assert_eq!(field.distances[i], expected, "{:?} reverse {} at {:?}", movement, reverse, pos);
+                    }
+                    assert_eq!(field.reached(), field.distances.iter().flatten().count());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn directions_lead_back_to_the_source_along_optimal_paths() {
+        for movement in MOVEMENTS {
+            for seed in 0..8 {
+                let grid = tiny_grid(6, 5, 0.2, false, seed);
+                let source = (0, 0);
+                for reverse in [false, true] {
+                    let field = distance_field(&grid, source, movement, reverse);
+                    let directions = field.direction_grid(movement.topology);
+                    assert_eq!(directions.cell(source), Some(0));
+                    for i in 0..grid.len() {
+                        let pos = grid.coord(i);
+                        let Some(distance) = field.distances[i] else {
+                            assert!(directions.is_wall(pos));
+                            continue;
+                        };
+                        let walk = follow(&directions, &grid, pos, movement);
+                        assert_eq!(walk.last(), Some(&source));
+                        // En sens inverse la marche elle-même est le chemin ; sinon c'est le chemin depuis la source, à l'envers
+                        let cost: i64 = walk.windows(2)
+                            .map(|pair| if reverse { movement.step_cost(&grid, pair[0], pair[1]) } else { movement.step_cost(&grid, pair[1], pair[0]) })
+                            .sum();
+                        assert_eq!(cost, distance, "{:?} reverse {} from {:?}", movement, reverse, pos);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn from_end_counts_the_cost_of_reaching_the_source() {
+        let grid = Grid::new(1, 3, vec![Some(1), Some(5), Some(9)]);
+        let movement = MOVEMENTS[0];
+        assert_eq!(distance_field(&grid, (0, 0), movement, false).distances, [Some(0), Some(5), Some(14)]);
+        let reverse = distance_field(&grid, (0, 0), movement, true);
+        assert_eq!(reverse.distances, [Some(0), Some(1), Some(6)]);
+        assert_eq!(reverse.direction_grid(movement.topology).cells(), [Some(0), Some(7), Some(7)]);
+        assert_eq!(reverse.heatmap().cells(), [Some(0), Some(42), Some(255)]);
+    }
+
+    #[test]
+    fn hex_directions_follow_the_row_shift() {
+        // Voisins de (2,2), ligne paire, puis de (1,2), ligne impaire décalée à droite
+        let even = [((1, 1), 8), ((1, 2), 2), ((2, 3), 3), ((3, 2), 4), ((3, 1), 6), ((2, 1), 7)];
+        let odd = [((0, 2), 8), ((0, 3), 2), ((1, 3), 3), ((2, 3), 4), ((2, 2), 6), ((1, 1), 7)];
+        for (from, cases) in [((2, 2), even), ((1, 2), odd)] {
+            for (to, code) in cases {
+                assert_eq!(direction_code(from, to, Topology::Hex), code, "{:?} -> {:?}", from, to);
+            }
+        }
+        assert_eq!(direction_code((1, 1), (0, 0), Topology::Eight), 8);
+        assert_eq!(direction_code((1, 1), (2, 2), Topology::Eight), 4);
+    }
+}
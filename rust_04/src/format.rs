@@ -0,0 +1,286 @@
+use crate::grid::{parse_map, CellWidth, Grid, ParseError, ParseOptions};
+use std::path::Path;
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// Encodage d'une carte sur disque
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MapFormat {
+    // Jetons hexadécimaux séparés par des blancs, une ligne par rangée
+    Text,
+    // Largeur en u32 little-endian suivie d'un octet par cellule
+    Raw,
+    // Niveaux de gris Netpbm 8 ou 16 bits, P2 ou P5 en lecture, P5 en écriture
+    Pgm,
+    // PNG en niveaux de gris 8 ou 16 bits
+    Png,
+}
+
+impl MapFormat {
+    pub fn name(self) -> &'static str {
+        match self {
+            MapFormat::Text => "text",
+            MapFormat::Raw => "raw",
+            MapFormat::Pgm => "PGM",
+            MapFormat::Png => "PNG",
+        }
+    }
+
+    pub fn from_extension(path: &Path) -> Option<MapFormat> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "txt" | "hex" => Some(MapFormat::Text),
+            "raw" | "bin" => Some(MapFormat::Raw),
+            "pgm" => Some(MapFormat::Pgm),
+            "png" => Some(MapFormat::Png),
+            _ => None,
+        }
+    }
+
+    // L'extension l'emporte, sinon on regarde les octets magiques
+    pub fn detect(path: &Path, data: &[u8]) -> MapFormat {
+        if let Some(format) = MapFormat::from_extension(path) {
+            return format;
+        }
+        if data.starts_with(PNG_SIGNATURE) {
+            MapFormat::Png
+        } else if data.len() > 2 && (data.starts_with(b"P2") || data.starts_with(b"P5")) && data[2].is_ascii_whitespace() {
+            MapFormat::Pgm
+        } else if std::str::from_utf8(data).is_ok() {
+            MapFormat::Text
+        } else {
+            MapFormat::Raw
+        }
+    }
+}
+
+fn malformed(message: impl Into<String>) -> ParseError {
+    ParseError::Malformed(message.into())
+}
+
+/// Lit une carte dans le format donné ; `wall_value` et `signed` s'appliquent aussi aux formats binaires.
+pub fn decode_map(data: &[u8], format: MapFormat, options: ParseOptions) -> Result<Grid, ParseError> {
+    let (cols, values, width) = match format {
+        MapFormat::Text => {
+            let text = std::str::from_utf8(data).map_err(|_| malformed("the map is not valid UTF-8 text"))?;
+            return parse_map(text, options);
+        }
+        MapFormat::Raw => {
+            let (cols, bytes) = decode_raw(data)?;
+            (cols, bytes.into_iter().map(u32::from).collect(), CellWidth::U8)
+        }
+        MapFormat::Pgm => decode_pgm(data)?,
+        MapFormat::Png => decode_png(data)?,
+    };
+    if cols == 0 || values.is_empty() {
+        return Err(ParseError::Empty);
+    }
+    let rows = values.len() / cols;
+    let cells = values.into_iter()
+        .map(|raw| match raw {
+            _ if Some(raw) == options.wall_value => None,
+            _ if options.signed => Some(width.to_signed(raw)),
+            _ => Some(raw as i64),
+        })
+        .collect();
+    Ok(Grid::new(rows, cols, cells).with_cell_type(width, options.signed))
+}
+
+fn decode_raw(data: &[u8]) -> Result<(usize, Vec<u8>), ParseError> {
+    let (header, body) = data.split_first_chunk::<4>().ok_or_else(|| malformed("raw map is missing its width header"))?;
+    let width = u32::from_le_bytes(*header) as usize;
+    if width == 0 && !body.is_empty() {
+        return Err(malformed("raw map header declares a width of 0"));
+    }
+    if width > 0 && body.len() % width != 0 {
+        return Err(malformed(format!("raw map has {} cells, not a multiple of the width {}", body.len(), width)));
+    }
+    Ok((width, body.to_vec()))
+}
+
+// En-tête PGM : nombres séparés par des blancs, commentaires `#` jusqu'à la fin de ligne
+fn pgm_header_field(data: &[u8], pos: &mut usize) -> Result<usize, ParseError> {
+    loop {
+        match data.get(*pos) {
+            Some(b'#') => {
+                while data.get(*pos).is_some_and(|&b| b != b'\n') {
+                    *pos += 1;
+                }
+            }
+            Some(b) if b.is_ascii_whitespace() => *pos += 1,
+            Some(_) => break,
+            None => return Err(malformed("truncated PGM header")),
+        }
+    }
+    let from = *pos;
+    while data.get(*pos).is_some_and(|b| b.is_ascii_digit()) {
+        *pos += 1;
+    }
+    std::str::from_utf8(&data[from..*pos])
+        .ok()
+        .and_then(|digits| digits.parse().ok())
+        .ok_or_else(|| malformed(format!("invalid number in PGM header at byte {}", from)))
+}
+
+fn decode_pgm(data: &[u8]) -> Result<(usize, Vec<u32>, CellWidth), ParseError> {
+    let binary = match data.get(..2) {
+        Some(b"P2") => false,
+        Some(b"P5") => true,
+        _ => return Err(malformed("PGM map must start with P2 or P5")),
+    };
+    let mut pos = 2;
+    let width = pgm_header_field(data, &mut pos)?;
+    let height = pgm_header_field(data, &mut pos)?;
+    let maxval = pgm_header_field(data, &mut pos)?;
+    if maxval == 0 || maxval > 65535 {
+        return Err(malformed(format!("PGM maxval {} is not supported, expected 1-65535", maxval)));
+    }
+    let cell_width = if maxval > 255 { CellWidth::U16 } else { CellWidth::U8 };
+    // Les dimensions viennent du fichier : leur produit peut déborder
+    let count = width.checked_mul(height)
+        .ok_or_else(|| malformed(format!("PGM dimensions {}x{} are too large", width, height)))?;
+
+    let values = if binary {
+        // Un seul blanc sépare l'en-tête des données ; 16 bits en big-endian
+        let body = data.get(pos + 1..).unwrap_or_default();
+        let bytes_per_cell = cell_width.digits() / 2;
+        let size = count.checked_mul(bytes_per_cell)
+            .ok_or_else(|| malformed(format!("PGM dimensions {}x{} are too large", width, height)))?;
+        if body.len() < size {
+            return Err(malformed(format!("PGM map has {} cells, expected {}", body.len() / bytes_per_cell, count)));
+        }
+        let values: Vec<u32> = body[..size]
+            .chunks(bytes_per_cell)
+            .map(|bytes| bytes.iter().fold(0, |acc, &b| acc << 8 | b as u32))
+            .collect();
+        if let Some(&value) = values.iter().find(|&&v| v as usize > maxval) {
+            return Err(malformed(format!("PGM value {} exceeds maxval {}", value, maxval)));
+        }
+        values
+    } else {
+        // Chaque valeur occupe au moins un octet : un en-tête mensonger ne réserve pas plus que le fichier
+        let mut values = Vec::with_capacity(count.min(data.len()));
+        for _ in 0..count {
+            let value = pgm_header_field(data, &mut pos)
+                .map_err(|_| malformed(format!("PGM map has {} cells, expected {}", values.len(), count)))?;
+            if value > maxval {
+                return Err(malformed(format!("PGM value {} exceeds maxval {}", value, maxval)));
+            }
+            values.push(value as u32);
+        }
+        values
+    };
+    Ok((width, values, cell_width))
+}
+
+fn decode_png(data: &[u8]) -> Result<(usize, Vec<u32>, CellWidth), ParseError> {
+    let mut decoder = png::Decoder::new(data);
+    decoder.set_transformations(png::Transformations::EXPAND);
+    let mut reader = decoder.read_info().map_err(|e| malformed(format!("invalid PNG: {}", e)))?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let frame = reader.next_frame(&mut buffer).map_err(|e| malformed(format!("invalid PNG: {}", e)))?;
+    // Le canal alpha éventuel est ignoré
+    let channels = match frame.color_type {
+        png::ColorType::Grayscale => 1,
+        png::ColorType::GrayscaleAlpha => 2,
+        other => return Err(malformed(format!("PNG must be grayscale, found {:?}", other))),
+    };
+    let (cell_width, sample) = match frame.bit_depth {
+        png::BitDepth::Sixteen => (CellWidth::U16, 2),
+        _ => (CellWidth::U8, 1),
+    };
+    let width = frame.width as usize;
+    let values = buffer[..frame.buffer_size()]
+        .chunks(frame.line_size)
+        .flat_map(|line| line.chunks(sample * channels).take(width))
+        .map(|pixel| pixel[..sample].iter().fold(0, |acc, &b| acc << 8 | b as u32))
+        .collect();
+    Ok((width, values, cell_width))
+}
+
+/// Sérialise une carte ; les murs deviennent `wall_value` dans les formats binaires.
+pub fn encode_map(grid: &Grid, format: MapFormat, wall_value: Option<u32>) -> Result<Vec<u8>, String> {
+    if format == MapFormat::Text {
+        return Ok(grid.to_string().into_bytes());
+    }
+    let width = grid.width();
+    if width == CellWidth::U32 || (width == CellWidth::U16 && format == MapFormat::Raw) {
+        return Err(format!("{}-digit cells cannot be written in the {} format", width.digits(), format.name()));
+    }
+    let values = (0..grid.len())
+        .map(|i| grid.raw(grid.coord(i)).or(wall_value))
+        .collect::<Option<Vec<u32>>>()
+        .ok_or("the map contains walls, pass --wall-value to choose the value written for them")?;
+    if let Some(&value) = values.iter().find(|&&v| v > width.max_raw()) {
+        return Err(format!("--wall-value {:X} does not fit in {}-digit cells", value, width.digits()));
+    }
+    // Échantillons big-endian, comme l'attendent PGM et PNG en 16 bits
+    let bytes: Vec<u8> = match width {
+        CellWidth::U8 => values.iter().map(|&v| v as u8).collect(),
+        _ => values.iter().flat_map(|&v| (v as u16).to_be_bytes()).collect(),
+    };
+
+    match format {
+        MapFormat::Raw => {
+            let width = u32::try_from(grid.cols()).map_err(|_| "the map is too wide for the raw format")?;
+            let mut out = width.to_le_bytes().to_vec();
+            out.extend_from_slice(&bytes);
+            Ok(out)
+        }
+        MapFormat::Pgm => {
+            let mut out = format!("P5\n{} {}\n{}\n", grid.cols(), grid.rows(), width.max_raw()).into_bytes();
+            out.extend_from_slice(&bytes);
+            Ok(out)
+        }
+        MapFormat::Png => {
+            let mut out = Vec::new();
+            let mut encoder = png::Encoder::new(&mut out, grid.cols() as u32, grid.rows() as u32);
+            encoder.set_color(png::ColorType::Grayscale);
+            encoder.set_depth(if width == CellWidth::U8 { png::BitDepth::Eight } else { png::BitDepth::Sixteen });
+            let mut writer = encoder.write_header().map_err(|e| e.to_string())?;
+            writer.write_image_data(&bytes).map_err(|e| e.to_string())?;
+            writer.finish().map_err(|e| e.to_string())?;
+            Ok(out)
+        }
+        MapFormat::Text => unreachable!(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(data: &[u8]) -> Result<Grid, ParseError> {
+        decode_map(data, MapFormat::Pgm, ParseOptions::default())
+    }
+
+    #[test]
+    fn pgm_overflowing_header_is_malformed() {
+        for data in [&b"P5 4294967296 4294967297 255\n"[..], b"P5 4294967296 2147483648 65535\n", b"P2 18446744073709551615 2 255\n1 2"] {
+            match decode(data) {
+                Err(ParseError::Malformed(message)) => assert!(message.contains("too large"), "{}", message),
+                other => panic!("expected a malformed error, got {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn pgm_truncated_ascii_body_is_malformed() {
+        match decode(b"P2 100000 100000 255\n1 2") {
+            Err(ParseError::Malformed(message)) => assert_eq!(message, "PGM map has 2 cells, expected 10000000000"),
+            other => panic!("expected a malformed error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn pgm_truncated_binary_body_is_malformed() {
+        assert!(matches!(decode(b"P5 2 2 255\n\x01\x02\x03"), Err(ParseError::Malformed(_))));
+    }
+
+    #[test]
+    fn pgm_ascii_map_is_decoded() {
+        let grid = decode(b"P2\n# commentaire\n3 2\n255\n1 2 3\n4 5 255\n").unwrap();
+        assert_eq!((grid.rows(), grid.cols()), (2, 3));
+        assert_eq!(grid.cell((1, 2)), Some(255));
+    }
+}
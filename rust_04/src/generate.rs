@@ -0,0 +1,474 @@
+use crate::grid::Grid;
+use crate::Coord;
+use clap::ValueEnum;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+// Chemin monotone aléatoire entre deux cellules, voisin en 4, 8 et hex
+fn carve_route(rng: &mut impl Rng, from: Coord, to: Coord) -> Vec<Coord> {
+    let mut cells = vec![from];
+    let mut current = from;
+    while current != to {
+        let move_row = current.1 == to.1 || (current.0 != to.0 && rng.gen_bool(0.5));
+        if move_row {
+            current.0 = if current.0 < to.0 { current.0 + 1 } else { current.0 - 1 };
+        } else {
+            current.1 = if current.1 < to.1 { current.1 + 1 } else { current.1 - 1 };
+        }
+        cells.push(current);
+    }
+    cells
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Distribution {
+    /// Independent uniform values
+    Uniform,
+    /// Independent values around --mean with --stddev
+    Normal,
+    /// Smooth Perlin noise with --scale and --octaves
+    Perlin,
+    /// --clusters regions of similar values with --stddev noise
+    Clustered,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Terrain {
+    pub distribution: Distribution,
+    pub mean: f64,
+    pub stddev: f64,
+    pub scale: f64,
+    pub octaves: u32,
+    pub clusters: usize,
+}
+
+impl Default for Terrain {
+    fn default() -> Self {
+        Terrain {
+            distribution: Distribution::Uniform,
+            mean: 127.0,
+            stddev: 40.0,
+            scale: 8.0,
+            octaves: 4,
+            clusters: 6,
+        }
+    }
+}
+
+// Les valeurs générées restent dans 01..FD, comme avec la distribution uniforme
+fn to_cell_value(value: f64) -> u8 {
+    value.round().clamp(1.0, 253.0) as u8
+}
+
+// Box-Muller : pas besoin de rand_distr pour une seule loi normale
+fn sample_normal(rng: &mut impl Rng, mean: f64, stddev: f64) -> f64 {
+    let u1: f64 = 1.0 - rng.gen::<f64>();
+    let u2: f64 = rng.gen();
+    mean + stddev * (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+}
+
+struct Perlin {
+    permutation: Vec<usize>,
+}
+
+impl Perlin {
+    fn new(rng: &mut impl Rng) -> Self {
+        let mut table: Vec<usize> = (0..256).collect();
+        table.shuffle(rng);
+        let permutation = table.iter().chain(table.iter()).copied().collect();
+        Perlin { permutation }
+    }
+
+    fn gradient(hash: usize, x: f64, y: f64) -> f64 {
+        match hash & 7 {
+            0 => x + y,
+            1 => x - y,
+            2 => -x + y,
+            3 => -x - y,
+            4 => x,
+            5 => -x,
+            6 => y,
+            _ => -y,
+        }
+    }
+
+    // Bruit dans [-1, 1] environ
+    fn noise(&self, x: f64, y: f64) -> f64 {
+        let fade = |t: f64| t * t * t * (t * (t * 6.0 - 15.0) + 10.0);
+        let lerp = |t: f64, a: f64, b: f64| a + t * (b - a);
+
+        let xi = (x.floor() as i64 & 255) as usize;
+        let yi = (y.floor() as i64 & 255) as usize;
+        let xf = x - x.floor();
+        let yf = y - y.floor();
+        let (u, v) = (fade(xf), fade(yf));
+
+        let p = &self.permutation;
+        let aa = p[p[xi] + yi];
+        let ab = p[p[xi] + yi + 1];
+        let ba = p[p[xi + 1] + yi];
+        let bb = p[p[xi + 1] + yi + 1];
+
+        lerp(v,
+             lerp(u, Self::gradient(aa, xf, yf), Self::gradient(ba, xf - 1.0, yf)),
+             lerp(u, Self::gradient(ab, xf, yf - 1.0), Self::gradient(bb, xf - 1.0, yf - 1.0)))
+    }
+
+    fn fractal(&self, x: f64, y: f64, octaves: u32) -> f64 {
+        let mut total = 0.0;
+        let mut amplitude = 1.0;
+        let mut frequency = 1.0;
+        let mut norm = 0.0;
+        for _ in 0..octaves.max(1) {
+            total += self.noise(x * frequency, y * frequency) * amplitude;
+            norm += amplitude;
+            amplitude *= 0.5;
+            frequency *= 2.0;
+        }
+        total / norm
+    }
+}
+
+fn terrain_values(rng: &mut impl Rng, w: usize, h: usize, terrain: &Terrain) -> Vec<Vec<u8>> {
+    match terrain.distribution {
+        Distribution::Uniform => (0..h)
+            .map(|_| (0..w).map(|_| rng.gen_range(0x01..0xFE)).collect())
+            .collect(),
+        Distribution::Normal => (0..h)
+            .map(|_| (0..w).map(|_| to_cell_value(sample_normal(rng, terrain.mean, terrain.stddev))).collect())
+            .collect(),
+        Distribution::Perlin => {
+            let perlin = Perlin::new(rng);
+            let scale = terrain.scale.max(f64::EPSILON);
+            let noise: Vec<Vec<f64>> = (0..h)
+                .map(|r| (0..w).map(|c| perlin.fractal(c as f64 / scale, r as f64 / scale, terrain.octaves)).collect())
+                .collect();
+            // Le bruit reste loin de ±1 : on l'étire sur toute la plage de valeurs
+            let low = noise.iter().flatten().copied().fold(f64::INFINITY, f64::min);
+            let high = noise.iter().flatten().copied().fold(f64::NEG_INFINITY, f64::max);
+            let span = (high - low).max(f64::EPSILON);
+            noise.iter()
+                .map(|row| row.iter().map(|n| to_cell_value((n - low) / span * 252.0 + 1.0)).collect())
+                .collect()
+        }
+        Distribution::Clustered => {
+            // Chaque cellule prend la valeur du centre le plus proche, bruitée
+            let centers: Vec<(f64, f64, f64)> = (0..terrain.clusters.max(1))
+                .map(|_| (rng.gen_range(0.0..h as f64), rng.gen_range(0.0..w as f64), rng.gen_range(1.0..=253.0)))
+                .collect();
+            (0..h)
+                .map(|r| (0..w).map(|c| {
+                    let (_, _, base) = centers.iter()
+                        .min_by(|a, b| {
+                            let da = (a.0 - r as f64).powi(2) + (a.1 - c as f64).powi(2);
+                            let db = (b.0 - r as f64).powi(2) + (b.1 - c as f64).powi(2);
+                            da.total_cmp(&db)
+                        })
+                        .copied()
+                        .unwrap_or((0.0, 0.0, terrain.mean));
+                    to_cell_value(sample_normal(rng, base, terrain.stddev))
+                }).collect())
+                .collect()
+        }
+    }
+}
+
+// `route` : cellules à relier (départ, étapes, arrivée), jamais coupées par un mur
+// Même graine et mêmes paramètres : carte identique octet pour octet
+pub fn generate_map(w: usize, h: usize, wall_density: f64, route: &[Coord], terrain: &Terrain, seed: u64) -> Grid {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    let mut grid = Vec::with_capacity(h);
+
+    let mut open = vec![vec![false; w]; h];
+    let stops: Vec<Coord> = route.iter().copied().filter(|&(r, c)| r < h && c < w).collect();
+    for pair in stops.windows(2) {
+        for (r, c) in carve_route(&mut rng, pair[0], pair[1]) {
+            open[r][c] = true;
+        }
+    }
+    let mut values = terrain_values(&mut rng, w, h, terrain);
+    // Départ le moins cher possible, arrivée la plus chère
+    if let (Some(&(sr, sc)), Some(&(er, ec))) = (stops.first(), stops.last()) {
+        values[sr][sc] = 0;
+        values[er][ec] = 0xFF;
+    }
+
+    for (r, open_row) in open.iter().enumerate() {
+        let mut row = Vec::with_capacity(w);
+        for (c, &is_open) in open_row.iter().enumerate() {
+            if !is_open && wall_density > 0.0 && rng.gen_bool(wall_density) {
+                row.push(None);
+                continue;
+            }
+            row.push(Some(values[r][c] as i64));
+        }
+        grid.push(row);
+    }
+    Grid::from_rows(grid)
+}
+
+/// Algorithme de labyrinthe pour `--maze`
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Maze {
+    /// Randomized Prim: many short dead ends
+    Prim,
+    /// Randomized Kruskal: uniform-looking, many short dead ends
+    Kruskal,
+    /// Depth-first carving: long winding corridors
+    RecursiveBacktracker,
+}
+
+// Ensembles disjoints pour Kruskal
+fn find(parent: &mut [usize], mut i: usize) -> usize {
+    while parent[i] != i {
+        parent[i] = parent[parent[i]];
+        i = parent[i];
+    }
+    i
+}
+
+// Salles voisines d'une salle, en coordonnées de salles
+fn room_neighbors((r, c): Coord, rows: usize, cols: usize) -> impl Iterator<Item = Coord> {
+    [(r.wrapping_sub(1), c), (r + 1, c), (r, c.wrapping_sub(1)), (r, c + 1)]
+        .into_iter()
+        .filter(move |&(nr, nc)| nr < rows && nc < cols)
+}
+
+// Passages ouverts entre salles voisines, l'arbre couvrant tiré par l'algorithme choisi
+fn maze_passages(rng: &mut impl Rng, rows: usize, cols: usize, maze: Maze) -> Vec<(Coord, Coord)> {
+    let mut passages = Vec::new();
+    let mut visited = vec![vec![false; cols]; rows];
+    match maze {
+        Maze::RecursiveBacktracker => {
+            // Pile explicite : un couloir peut traverser tout le labyrinthe
+            let mut stack = vec![(0, 0)];
+            visited[0][0] = true;
+            while let Some(&room) = stack.last() {
+                let next: Vec<Coord> = room_neighbors(room, rows, cols).filter(|&(r, c)| !visited[r][c]).collect();
+                match next.choose(rng) {
+                    Some(&(r, c)) => {
+                        visited[r][c] = true;
+                        passages.push((room, (r, c)));
+                        stack.push((r, c));
+                    }
+                    None => {
+                        stack.pop();
+                    }
+                }
+            }
+        }
+        Maze::Prim => {
+            let mut frontier: Vec<(Coord, Coord)> = room_neighbors((0, 0), rows, cols).map(|next| ((0, 0), next)).collect();
+            visited[0][0] = true;
+            while !frontier.is_empty() {
+                let (from, (r, c)) = frontier.swap_remove(rng.gen_range(0..frontier.len()));
+                if visited[r][c] {
+                    continue;
+                }
+                visited[r][c] = true;
+                passages.push((from, (r, c)));
+                frontier.extend(room_neighbors((r, c), rows, cols).filter(|&(nr, nc)| !visited[nr][nc]).map(|next| ((r, c), next)));
+            }
+        }
+        Maze::Kruskal => {
+            let mut edges: Vec<(Coord, Coord)> = (0..rows)
+                .flat_map(|r| (0..cols).map(move |c| (r, c)))
+                .flat_map(|room| [(room, (room.0 + 1, room.1)), (room, (room.0, room.1 + 1))])
+                .filter(|&(_, (r, c))| r < rows && c < cols)
+                .collect();
+            edges.shuffle(rng);
+            let mut parent: Vec<usize> = (0..rows * cols).collect();
+            for (a, b) in edges {
+                let (ra, rb) = (find(&mut parent, a.0 * cols + a.1), find(&mut parent, b.0 * cols + b.1));
+                if ra != rb {
+                    parent[ra] = rb;
+                    passages.push((a, b));
+                }
+            }
+        }
+    }
+    passages
+}
+
+// Ouvre les étapes tombées sur un mur sans créer de boucle, l'arbre est reconstruit cellule par cellule à la Kruskal.
+// Une étape sur un pilier touche quatre murs entre salles : en ouvrir deux opposés la relie aux quatre salles en
+// diagonale, les deux autres restent fermés. Salles et murs ainsi fixés sont ouverts d'office, puis les passages du
+// labyrinthe et enfin les autres murs dans un ordre aléatoire, chacun seulement s'il relie des composantes distinctes.
+// Seules des étapes qui s'enferment elles-mêmes (voisines, ou piliers aux choix incompatibles) forment encore une boucle.
+fn open_stops(rng: &mut impl Rng, open: &mut [Vec<bool>], stops: &[Coord]) {
+    let (h, w) = (open.len(), open[0].len());
+    let is_pillar = |(r, c): Coord| r % 2 == 1 && c % 2 == 1;
+    // `Some(true)` ouvert d'office, `Some(false)` fermé, `None` laissé à Kruskal
+    let mut fixed: Vec<Vec<Option<bool>>> = vec![vec![None; w]; h];
+    for &(r, c) in stops {
+        fixed[r][c] = Some(true);
+    }
+    for &(r, c) in stops.iter().filter(|&&pos| is_pillar(pos)) {
+        let vertical: Vec<Coord> = [(r - 1, c), (r + 1, c)].into_iter().filter(|&(nr, _)| nr < h).collect();
+        let horizontal: Vec<Coord> = [(r, c - 1), (r, c + 1)].into_iter().filter(|&(_, nc)| nc < w).collect();
+        // La paire choisie doit respecter les murs déjà fixés par les étapes voisines
+        let fits = |pair: &[Coord], other: &[Coord]| {
+            pair.iter().all(|&(nr, nc)| fixed[nr][nc] != Some(false)) && other.iter().all(|&(nr, nc)| fixed[nr][nc] != Some(true))
+        };
+        let (pair, other) = match (fits(&vertical, &horizontal), fits(&horizontal, &vertical)) {
+            (true, false) => (vertical, horizontal),
+            (false, true) => (horizontal, vertical),
+            _ if rng.gen_bool(0.5) => (vertical, horizontal),
+            _ => (horizontal, vertical),
+        };
+        for (nr, nc) in pair {
+            fixed[nr][nc] = Some(true);
+        }
+        for (nr, nc) in other {
+            fixed[nr][nc].get_or_insert(false);
+        }
+    }
+
+    let previous: Vec<Vec<bool>> = open.to_vec();
+    open.iter_mut().for_each(|row| row.fill(false));
+    let mut parent: Vec<usize> = (0..h * w).collect();
+    let mut connect = |open: &mut [Vec<bool>], (r, c): Coord, forced: bool| {
+        let roots: Vec<usize> = room_neighbors((r, c), h, w)
+            .filter(|&(nr, nc)| open[nr][nc])
+            .map(|(nr, nc)| find(&mut parent, nr * w + nc))
+            .collect();
+        let mut distinct = roots.clone();
+        distinct.sort_unstable();
+        distinct.dedup();
+        if !forced && (distinct.len() < 2 || distinct.len() < roots.len()) {
+            return;
+        }
+        open[r][c] = true;
+        for root in distinct {
+            parent[root] = r * w + c;
+        }
+    };
+
+    let cells: Vec<Coord> = (0..h).flat_map(|r| (0..w).map(move |c| (r, c))).collect();
+    for &(r, c) in cells.iter().filter(|&&(r, c)| (r % 2 == 0 && c % 2 == 0) || fixed[r][c] == Some(true)) {
+        connect(open, (r, c), true);
+    }
+    let (passages, mut walls): (Vec<Coord>, Vec<Coord>) = cells.into_iter()
+        .filter(|&(r, c)| !open[r][c] && !is_pillar((r, c)) && fixed[r][c].is_none())
+        .partition(|&(r, c)| previous[r][c]);
+    walls.shuffle(rng);
+    for pos in passages.into_iter().chain(walls) {
+        connect(open, pos, false);
+    }
+}
+
+/// Labyrinthe en 4-voisinage : les salles sont les cellules de ligne et colonne paires, les murs sont des `XX`.
+/// Avec `loops` à 0 le labyrinthe est parfait (un seul chemin entre deux salles) ; sinon chaque mur restant
+/// entre deux salles tombe avec cette probabilité. Les cellules de `route` tombées sur un mur sont ouvertes et
+/// reliées au labyrinthe sans y ajouter de boucle. Les cellules ouvertes valent 01, ou suivent `terrain` s'il est donné.
+pub fn generate_maze(w: usize, h: usize, maze: Maze, loops: f64, route: &[Coord], terrain: Option<&Terrain>, seed: u64) -> Grid {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    let (rows, cols) = (h.div_ceil(2), w.div_ceil(2));
+    let mut open = vec![vec![false; w]; h];
+    for r in 0..rows {
+        for c in 0..cols {
+            open[r * 2][c * 2] = true;
+        }
+    }
+    for ((r1, c1), (r2, c2)) in maze_passages(&mut rng, rows, cols, maze) {
+        open[r1 + r2][c1 + c2] = true;
+    }
+    let stops: Vec<Coord> = route.iter().copied().filter(|&(r, c)| r < h && c < w).collect();
+    if stops.iter().any(|&(r, c)| !open[r][c]) {
+        open_stops(&mut rng, &mut open, &stops);
+    }
+    if loops > 0.0 {
+        for (r, open_row) in open.iter_mut().enumerate() {
+            for (c, cell) in open_row.iter_mut().enumerate() {
+                // Mur entre deux salles, à l'horizontale ou à la verticale
+                let between = (r % 2 == 1 && c % 2 == 0 && r + 1 < h) || (r % 2 == 0 && c % 2 == 1 && c + 1 < w);
+                if between && !*cell && rng.gen_bool(loops.min(1.0)) {
+                    *cell = true;
+                }
+            }
+        }
+    }
+
+    let values = terrain.map(|terrain| terrain_values(&mut rng, w, h, terrain));
+    let cells = (0..h)
+        .flat_map(|r| (0..w).map(move |c| (r, c)))
+        .map(|(r, c)| open[r][c].then(|| values.as_ref().map_or(1, |values| values[r][c]) as i64))
+        .collect();
+    Grid::new(h, w, cells)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Nombre de chemins simples de `from` à `to` en 4-voisinage, recherche exhaustive
+    fn simple_paths(grid: &Grid, from: Coord, to: Coord, visited: &mut Vec<bool>) -> usize {
+        if from == to {
+            return 1;
+        }
+        visited[grid.index(from)] = true;
+        let next: Vec<Coord> = room_neighbors(from, grid.rows(), grid.cols())
+            .filter(|&next| !grid.is_wall(next) && !visited[grid.index(next)])
+            .collect();
+        let count = next.into_iter().map(|next| simple_paths(grid, next, to, visited)).sum();
+        visited[grid.index(from)] = false;
+        count
+    }
+
+    #[test]
+    fn generated_maps_price_the_chosen_endpoints() {
+        for (start, end) in [((0, 0), (9, 11)), ((3, 8), (7, 2)), ((9, 0), (0, 11))] {
+            for seed in 0..5 {
+                let grid = generate_map(12, 10, 0.3, &[start, (5, 5), end], &Terrain::default(), seed);
+                assert_eq!((grid.cell(start), grid.cell(end)), (Some(0), Some(0xFF)), "{:?} -> {:?}", start, end);
+                let same = generate_map(12, 10, 0.3, &[start, (5, 5), end], &Terrain::default(), seed);
+                assert_eq!(grid, same);
+            }
+        }
+        // Les coins ne sont pas forcés quand ils ne sont pas des extrémités
+        let corners = [(0, 0), (9, 11)];
+        let moved = (0..20)
+            .map(|seed| generate_map(12, 10, 0.0, &[(4, 4), (6, 6)], &Terrain::default(), seed))
+            .filter(|grid| (grid.cell(corners[0]), grid.cell(corners[1])) != (Some(0), Some(0xFF)))
+            .count();
+        assert!(moved > 0);
+    }
+
+    #[test]
+    fn perfect_maze_has_a_single_path_between_stops() {
+        for h in 2..=8 {
+            for w in 2..=8 {
+                // Étape au milieu sur un pilier, et fin sur le dernier coin qui peut en être un
+                let route = [(0, 0), ((h / 2) | 1, (w / 2) | 1), (h - 1, w - 1)];
+                for maze in [Maze::Prim, Maze::Kruskal, Maze::RecursiveBacktracker] {
+                    for seed in 0..20 {
+                        let grid = generate_maze(w, h, maze, 0.0, &route, None, seed);
+                        let mut visited = vec![false; grid.len()];
+                        for (from, to) in [(route[0], route[1]), (route[1], route[2]), (route[0], route[2])] {
+                            assert_eq!(simple_paths(&grid, from, to, &mut visited), 1,
+                                       "{:?} {}x{} seed {} from {:?} to {:?}", maze, w, h, seed, from, to);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn perfect_maze_is_a_tree() {
+        for (w, h) in [(9, 7), (10, 6), (12, 12)] {
+            for maze in [Maze::Prim, Maze::Kruskal, Maze::RecursiveBacktracker] {
+                let route = [(0, 0), (1, 1), (3, 2), (h - 1, w - 1)];
+                let grid = generate_maze(w, h, maze, 0.0, &route, None, 7);
+                let open: Vec<Coord> = (0..h).flat_map(|r| (0..w).map(move |c| (r, c))).filter(|&pos| !grid.is_wall(pos)).collect();
+                let edges: usize = open.iter()
+                    .map(|&pos| room_neighbors(pos, h, w).filter(|&next| next > pos && !grid.is_wall(next)).count())
+                    .sum();
+                // Connexe avec une arête de moins que de cellules : un arbre, toutes les salles comprises
+                let reached = open.iter().filter(|&&pos| simple_paths(&grid, (0, 0), pos, &mut vec![false; grid.len()]) == 1).count();
+                assert_eq!((reached, edges + 1), (open.len(), open.len()), "{:?} {}x{}", maze, w, h);
+            }
+        }
+    }
+}
@@ -0,0 +1,455 @@
+use crate::Coord;
+use std::fmt;
+
+/// Largeur d'une cellule dans le format texte : 2, 4 ou 8 chiffres hexadécimaux
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum CellWidth {
+    #[default]
+    U8,
+    U16,
+    U32,
+}
+
+impl CellWidth {
+    pub fn digits(self) -> usize {
+        match self {
+            CellWidth::U8 => 2,
+            CellWidth::U16 => 4,
+            CellWidth::U32 => 8,
+        }
+    }
+
+    pub fn bits(self) -> u32 {
+        self.digits() as u32 * 4
+    }
+
+    // Plus petite largeur capable d'écrire `digits` chiffres
+    pub fn for_digits(digits: usize) -> Option<CellWidth> {
+        match digits {
+            0..=2 => Some(CellWidth::U8),
+            3..=4 => Some(CellWidth::U16),
+            5..=8 => Some(CellWidth::U32),
+            _ => None,
+        }
+    }
+
+    pub fn max_raw(self) -> u32 {
+        u32::MAX >> (32 - self.bits())
+    }
+
+    // Interprétation en complément à deux sur la largeur de la cellule
+    pub fn to_signed(self, raw: u32) -> i64 {
+        let raw = raw as i64;
+        if raw > (self.max_raw() >> 1) as i64 {
+            raw - (1i64 << self.bits())
+        } else {
+            raw
+        }
+    }
+
+    pub fn to_raw(self, value: i64) -> u32 {
+        (value as u64 & self.max_raw() as u64) as u32
+    }
+}
+
+/// Grille de coûts rectangulaire, `None` marquant un mur
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Grid {
+    rows: usize,
+    cols: usize,
+    cells: Vec<Option<i64>>,
+    width: CellWidth,
+    signed: bool,
+}
+
+impl Grid {
+    pub fn new(rows: usize, cols: usize, cells: Vec<Option<i64>>) -> Self {
+        assert_eq!(cells.len(), rows * cols, "grid cell count does not match its dimensions");
+        Grid { rows, cols, cells, width: CellWidth::U8, signed: false }
+    }
+
+    // Les lignes doivent toutes avoir la même longueur
+    pub fn from_rows(rows: Vec<Vec<Option<i64>>>) -> Self {
+        let cols = rows.first().map_or(0, |row| row.len());
+        assert!(rows.iter().all(|row| row.len() == cols), "grid rows have different lengths");
+        Grid::new(rows.len(), cols, rows.into_iter().flatten().collect())
+    }
+
+    // Largeur des jetons et signe, utilisés pour l'affichage et l'export
+    pub fn with_cell_type(mut self, width: CellWidth, signed: bool) -> Self {
+        self.width = width;
+        self.signed = signed;
+        self
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn width(&self) -> CellWidth {
+        self.width
+    }
+
+    pub fn is_signed(&self) -> bool {
+        self.signed
+    }
+
+    pub fn contains(&self, (r, c): Coord) -> bool {
+        r < self.rows && c < self.cols
+    }
+
+    pub fn index(&self, (r, c): Coord) -> usize {
+        r * self.cols + c
+    }
+
+    pub fn coord(&self, index: usize) -> Coord {
+        (index / self.cols, index % self.cols)
+    }
+
+    pub fn cell(&self, pos: Coord) -> Option<i64> {
+        self.cells[self.index(pos)]
+    }
+
+    pub fn set(&mut self, pos: Coord, cell: Option<i64>) {
+        let index = self.index(pos);
+        self.cells[index] = cell;
+    }
+
+    pub fn is_wall(&self, pos: Coord) -> bool {
+        self.cell(pos).is_none()
+    }
+
+    pub fn cells(&self) -> &[Option<i64>] {
+        &self.cells
+    }
+
+    pub fn row(&self, r: usize) -> &[Option<i64>] {
+        &self.cells[r * self.cols..(r + 1) * self.cols]
+    }
+
+    // Plus petite valeur hors murs
+    pub fn min_value(&self) -> Option<i64> {
+        self.cells.iter().flatten().copied().min()
+    }
+
+    pub fn max_value(&self) -> Option<i64> {
+        self.cells.iter().flatten().copied().max()
+    }
+
+    pub fn has_negative(&self) -> bool {
+        self.cells.iter().flatten().any(|&v| v < 0)
+    }
+
+    // Valeur telle qu'écrite sur disque (complément à deux en mode signé)
+    pub fn raw(&self, pos: Coord) -> Option<u32> {
+        self.cell(pos).map(|v| self.width.to_raw(v))
+    }
+
+    // Position de la valeur dans l'intervalle représentable, ramenée sur 0-255
+    pub fn intensity(&self, pos: Coord) -> Option<u8> {
+        let max = self.width.max_raw() as i64;
+        self.cell(pos).map(|v| {
+            let v = if self.signed { v + max / 2 + 1 } else { v };
+            (v.clamp(0, max) * 255 / max) as u8
+        })
+    }
+
+    // Jeton tel qu'écrit dans le format texte : `1F` ou `XX` (`001F` ou `XXXX` en 16 bits)
+    pub fn token(&self, pos: Coord) -> String {
+        let digits = self.width.digits();
+        match self.raw(pos) {
+            Some(raw) => format!("{:0digits$X}", raw),
+            None => "X".repeat(digits),
+        }
+    }
+}
+
+// Format texte relu par `parse_map`, une ligne par rangée
+impl fmt::Display for Grid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for r in 0..self.rows {
+            if r > 0 {
+                writeln!(f)?;
+            }
+            let tokens: Vec<String> = (0..self.cols).map(|c| self.token((r, c))).collect();
+            write!(f, "{}", tokens.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+pub const WALL_TOKEN: &str = "XX";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseIssue {
+    pub line: usize,
+    pub column: usize,
+    pub token: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    Empty,
+    Invalid(Vec<ParseIssue>),
+    Malformed(String),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Empty => write!(f, "Invalid map format: the map contains no cells"),
+            ParseError::Malformed(message) => write!(f, "Invalid map format: {}", message),
+            ParseError::Invalid(issues) => {
+                write!(f, "Invalid map format ({} error{}):", issues.len(), if issues.len() > 1 { "s" } else { "" })?;
+                for issue in issues {
+                    write!(f, "\n  line {}, column {}: {}", issue.line, issue.column, issue.message)?;
+                    if !issue.token.is_empty() {
+                        write!(f, " '{}'", issue.token)?;
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+// Découpe une ligne en jetons avec leur colonne (1-based, en caractères)
+fn tokens_with_columns(line: &str) -> Vec<(usize, &str)> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    for (column, (offset, ch)) in line.char_indices().enumerate() {
+        match (ch.is_whitespace(), start) {
+            (false, None) => start = Some((column + 1, offset)),
+            (true, Some((col, from))) => {
+                tokens.push((col, &line[from..offset]));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some((col, from)) = start {
+        tokens.push((col, &line[from..]));
+    }
+    tokens
+}
+
+/// Options de lecture communes à tous les formats de carte
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ParseOptions {
+    // Valeur brute traitée comme un mur, en plus des jetons `XX`
+    pub wall_value: Option<u32>,
+    // N'accepter que le format écrit par `--output`
+    pub strict: bool,
+    // Valeurs en complément à deux sur la largeur des jetons
+    pub signed: bool,
+}
+
+fn is_wall_token(token: &str, strict: bool) -> bool {
+    matches!(token.len(), 2 | 4 | 8)
+        && token.chars().all(|ch| ch == 'X' || (!strict && ch == 'x'))
+}
+
+// Valeur brute d'une cellule et son nombre de chiffres, ou description de ce qui ne va pas avec le jeton
+fn parse_cell(token: &str, strict: bool) -> Result<Option<(u32, usize)>, &'static str> {
+    if is_wall_token(token, strict) {
+        return Ok(None);
+    }
+    if strict && (!matches!(token.len(), 2 | 4 | 8) || !token.chars().all(|ch| ch.is_ascii_digit() || ('A'..='F').contains(&ch))) {
+        return Err("non-canonical token, expected 2, 4 or 8 uppercase hex digits or XX");
+    }
+
+    let digits = token.strip_prefix("0x").or_else(|| token.strip_prefix("0X")).unwrap_or(token);
+    if digits.is_empty() || digits.starts_with('+') {
+        return Err("invalid hex value");
+    }
+    if digits.len() > 8 {
+        return Err("hex value wider than 8 digits");
+    }
+    let value = u32::from_str_radix(digits, 16).map_err(|_| "invalid hex value")?;
+    Ok(Some((value, digits.len())))
+}
+
+// `XX` marque toujours un mur ; `wall_value` permet d'en faire autant d'une valeur brute.
+// La largeur des cellules (8, 16 ou 32 bits) suit le jeton le plus long.
+pub fn parse_map(map_data: &str, options: ParseOptions) -> Result<Grid, ParseError> {
+    let strict = options.strict;
+    let mut grid = Vec::new();
+    let mut issues = Vec::new();
+    let mut cols = 0;
+    let mut widest = 0;
+    
+    for (line_index, raw_line) in map_data.lines().enumerate() {
+        let line_number = line_index + 1;
+        let line = match raw_line.find('#') {
+            Some(comment) => {
+                if strict {
+                    issues.push(ParseIssue {
+                        line: line_number,
+                        column: raw_line[..comment].chars().count() + 1,
+                        token: String::new(),
+                        message: "comments are not allowed in strict mode".to_string(),
+                    });
+                }
+                &raw_line[..comment]
+            }
+            None => raw_line,
+        };
+
+        let tokens = tokens_with_columns(line);
+        if tokens.is_empty() {
+            if strict && raw_line.trim().is_empty() && line_number < map_data.lines().count() {
+                issues.push(ParseIssue {
+                    line: line_number,
+                    column: 1,
+                    token: String::new(),
+                    message: "blank lines are not allowed in strict mode".to_string(),
+                });
+            }
+            continue;
+        }
+
+        let mut row = Vec::with_capacity(tokens.len());
+        for &(column, token) in &tokens {
+            match parse_cell(token, strict) {
+                Ok(cell) => {
+                    widest = widest.max(cell.map_or(0, |(_, digits)| digits));
+                    row.push((line_number, column, token, cell));
+                }
+                Err(message) => issues.push(ParseIssue {
+                    line: line_number,
+                    column,
+                    token: token.to_string(),
+                    message: message.to_string(),
+                }),
+            }
+        }
+
+        if cols == 0 {
+            cols = tokens.len();
+        } else if tokens.len() != cols {
+            let (column, token) = match tokens.get(cols) {
+                Some(&(column, token)) => (column, token.to_string()),
+                None => (line.trim_end().chars().count() + 1, String::new()),
+            };
+            issues.push(ParseIssue {
+                line: line_number,
+                column,
+                token,
+                message: format!("row has {} cells, expected {}", tokens.len(), cols),
+            });
+        }
+        grid.push(row);
+    }
+
+    let width = CellWidth::for_digits(widest).unwrap_or(CellWidth::U32);
+    let grid: Vec<Vec<Option<i64>>> = grid.into_iter()
+        .map(|row| row.into_iter().map(|(line, column, token, cell)| {
+            if strict && token.len() != width.digits() {
+                issues.push(ParseIssue {
+                    line,
+                    column,
+                    token: token.to_string(),
+                    message: format!("token width differs from the rest of the map, expected {} digits", width.digits()),
+                });
+            }
+            let (raw, _) = cell?;
+            if Some(raw) == options.wall_value {
+                return None;
+            }
+            Some(if options.signed { width.to_signed(raw) } else { raw as i64 })
+        }).collect())
+        .collect();
+
+    if !issues.is_empty() {
+        issues.sort_by_key(|issue| (issue.line, issue.column));
+        return Err(ParseError::Invalid(issues));
+    }
+    if grid.is_empty() {
+        return Err(ParseError::Empty);
+    }
+    Ok(Grid::from_rows(grid).with_cell_type(width, options.signed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STRICT: ParseOptions = ParseOptions { wall_value: None, strict: true, signed: false };
+
+    // (ligne, colonne, jeton, message) de chaque problème relevé
+    fn issues(map_data: &str, options: ParseOptions) -> Vec<(usize, usize, String, String)> {
+        match parse_map(map_data, options) {
+            Err(ParseError::Invalid(issues)) => issues.into_iter().map(|i| (i.line, i.column, i.token, i.message)).collect(),
+            other => panic!("expected parse issues, got {:?}", other),
+        }
+    }
+
+    fn issue(line: usize, column: usize, token: &str, message: &str) -> (usize, usize, String, String) {
+        (line, column, token.to_string(), message.to_string())
+    }
+
+    #[test]
+    fn lenient_map_is_decoded() {
+        let grid = parse_map("# header\n0x1F XX\n\nff  xx # trailing\n", ParseOptions::default()).unwrap();
+        assert_eq!((grid.rows(), grid.cols(), grid.width()), (2, 2, CellWidth::U8));
+        assert_eq!(grid.cells(), &[Some(0x1F), None, Some(0xFF), None]);
+
+        let signed = parse_map("FF 01\n8000 7FFF", ParseOptions { signed: true, ..ParseOptions::default() }).unwrap();
+        assert_eq!(signed.width(), CellWidth::U16);
+        assert_eq!(signed.cells(), &[Some(0xFF), Some(1), Some(-0x8000), Some(0x7FFF)]);
+
+        let walls = parse_map("01 FF\nFF 02", ParseOptions { wall_value: Some(0xFF), ..ParseOptions::default() }).unwrap();
+        assert_eq!(walls.cells(), &[Some(1), None, None, Some(2)]);
+    }
+
+    #[test]
+    fn every_issue_is_located() {
+        assert_eq!(issues("01 02\n03 zz 04\n05\n1234567890 06", ParseOptions::default()), vec![
+            issue(2, 4, "zz", "invalid hex value"),
+            issue(2, 7, "04", "row has 3 cells, expected 2"),
+            issue(3, 3, "", "row has 1 cells, expected 2"),
+            issue(4, 1, "1234567890", "hex value wider than 8 digits"),
+        ]);
+        // Les colonnes comptent les caractères, pas les octets
+        assert_eq!(issues("é1 02", ParseOptions::default()), vec![issue(1, 1, "é1", "invalid hex value")]);
+        assert_eq!(issues("01  0x", ParseOptions::default()), vec![issue(1, 5, "0x", "invalid hex value")]);
+    }
+
+    #[test]
+    fn strict_mode_rejects_what_output_never_writes() {
+        assert_eq!(issues("01 02 # note\n03 04", STRICT), vec![issue(1, 7, "", "comments are not allowed in strict mode")]);
+        assert_eq!(issues("01 02\n\n03 04", STRICT), vec![issue(2, 1, "", "blank lines are not allowed in strict mode")]);
+        let non_canonical = "non-canonical token, expected 2, 4 or 8 uppercase hex digits or XX";
+        assert_eq!(issues("01 0a\nxx 0x04", STRICT), vec![
+            issue(1, 4, "0a", non_canonical),
+            issue(2, 1, "xx", non_canonical),
+            issue(2, 4, "0x04", non_canonical),
+        ]);
+        let mixed = "token width differs from the rest of the map, expected 4 digits";
+        assert_eq!(issues("01 0002\n0003 04", STRICT), vec![issue(1, 1, "01", mixed), issue(2, 6, "04", mixed)]);
+        // Une ligne vide finale reste admise, comme celle écrite par `--output`
+        assert!(parse_map("01 02\n03 04\n\n", STRICT).is_ok());
+    }
+
+    #[test]
+    fn map_without_cells_is_empty() {
+        assert!(matches!(parse_map("# nothing\n\n   \n", ParseOptions::default()), Err(ParseError::Empty)));
+        let message = parse_map("01 02\n03", ParseOptions::default()).unwrap_err().to_string();
+        assert_eq!(message, "Invalid map format (1 error):\n  line 2, column 3: row has 1 cells, expected 2");
+    }
+}
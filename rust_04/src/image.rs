@@ -0,0 +1,352 @@
+use crate::grid::Grid;
+use crate::render::cell_rgb;
+use crate::topology::Topology;
+use crate::Coord;
+use std::fmt::Write;
+use std::path::Path;
+
+pub type Rgb = (u8, u8, u8);
+
+pub const MIN_PATH_RGB: Rgb = (255, 255, 255);
+pub const MAX_PATH_RGB: Rgb = (220, 20, 20);
+// Mêmes teintes que `render::ALTERNATIVE_ANSI`
+pub const ALTERNATIVE_RGB: [Rgb; 6] = [(255, 255, 255), (255, 215, 0), (0, 215, 255), (215, 0, 215), (0, 200, 0), (40, 90, 255)];
+const START_RGB: Rgb = (0, 200, 0);
+const END_RGB: Rgb = (0, 96, 255);
+const OUTLINE_RGB: Rgb = (0, 0, 0);
+const MIN_CELL_SIZE: u32 = 4;
+/// Surface maximale d'un PNG (8192 x 8192) ; au-delà, les cellules sont dessinées plus petites que `cell_size`
+pub const MAX_PNG_PIXELS: u64 = 1 << 26;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ImageFormat {
+    Svg,
+    Png,
+}
+
+impl ImageFormat {
+    pub fn from_extension(path: &Path) -> Option<ImageFormat> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "svg" => Some(ImageFormat::Svg),
+            "png" => Some(ImageFormat::Png),
+            _ => None,
+        }
+    }
+}
+
+/// Chemin superposé à la grille, dessiné dans l'ordre de la liste
+#[derive(Clone, Debug)]
+pub struct Overlay<'a> {
+    pub path: &'a [Coord],
+    pub color: Rgb,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct ImageOptions {
+    pub topology: Topology,
+    pub cell_size: u32,
+    pub labels: bool,
+}
+
+// Géométrie commune au SVG et au PNG : les lignes impaires sont décalées en hexagonal
+struct Layout {
+    cell: f64,
+    hex: bool,
+    width: u64,
+    height: u64,
+}
+
+impl Layout {
+    fn new(grid: &Grid, topology: Topology, cell_size: u32) -> Self {
+        let hex = topology == Topology::Hex && grid.rows() > 1;
+        let cell = cell_size.max(MIN_CELL_SIZE) as u64;
+        let shift = if hex { cell / 2 } else { 0 };
+        Layout {
+            cell: cell as f64,
+            hex,
+            width: grid.cols() as u64 * cell + shift,
+            height: grid.rows() as u64 * cell,
+        }
+    }
+
+    fn origin(&self, (r, c): Coord) -> (f64, f64) {
+        let shift = if self.hex && r % 2 == 1 { self.cell / 2.0 } else { 0.0 };
+        (c as f64 * self.cell + shift, r as f64 * self.cell)
+    }
+
+    fn center(&self, pos: Coord) -> (f64, f64) {
+        let (x, y) = self.origin(pos);
+        (x + self.cell / 2.0, y + self.cell / 2.0)
+    }
+}
+
+fn label_rgb((r, g, b): Rgb) -> Rgb {
+    if 0.299 * r as f64 + 0.587 * g as f64 + 0.114 * b as f64 > 128.0 {
+        (0, 0, 0)
+    } else {
+        (255, 255, 255)
+    }
+}
+
+fn hex_color((r, g, b): Rgb) -> String {
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+pub fn render_svg(grid: &Grid, overlays: &[Overlay], start: Coord, end: Coord, options: &ImageOptions) -> String {
+    let layout = Layout::new(grid, options.topology, options.cell_size);
+    let cell = layout.cell;
+    let mut svg = String::new();
+    let _ = writeln!(svg, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+                     w = layout.width, h = layout.height);
+
+    let _ = writeln!(svg, r#"<g shape-rendering="crispEdges">"#);
+    for r in 0..grid.rows() {
+        for c in 0..grid.cols() {
+            let (x, y) = layout.origin((r, c));
+            let _ = writeln!(svg, r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#,
+                             x, y, cell, cell, hex_color(cell_rgb(grid.intensity((r, c)))));
+        }
+    }
+    let _ = writeln!(svg, "</g>");
+
+    if options.labels {
+        let _ = writeln!(svg, r#"<g font-family="monospace" font-size="{}" text-anchor="middle" dominant-baseline="central">"#, cell * 0.4);
+        for r in 0..grid.rows() {
+            for c in 0..grid.cols() {
+                let (x, y) = layout.center((r, c));
+                let color = label_rgb(cell_rgb(grid.intensity((r, c))));
+                let _ = writeln!(svg, r#"<text x="{}" y="{}" fill="{}">{}</text>"#, x, y, hex_color(color), grid.token((r, c)));
+            }
+        }
+        let _ = writeln!(svg, "</g>");
+    }
+
+    for overlay in overlays.iter().filter(|o| !o.path.is_empty()) {
+        let points: Vec<String> = overlay.path.iter()
+            .map(|&pos| {
+                let (x, y) = layout.center(pos);
+                format!("{},{}", x, y)
+            })
+            .collect();
+        let _ = writeln!(svg, r#"<polyline points="{}" fill="none" stroke="{}" stroke-width="{}" stroke-linecap="round" stroke-linejoin="round" stroke-opacity="0.85"/>"#,
+                         points.join(" "), hex_color(overlay.color), cell / 6.0);
+    }
+
+    for (pos, color) in [(start, START_RGB), (end, END_RGB)] {
+        let (x, y) = layout.center(pos);
+        let _ = writeln!(svg, r#"<circle cx="{}" cy="{}" r="{}" fill="{}" stroke="{}" stroke-width="{}"/>"#,
+                         x, y, cell / 4.0, hex_color(color), hex_color(OUTLINE_RGB), cell / 16.0);
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+// Police bitmap 3x5 pour les étiquettes hexadécimales
+fn glyph(ch: char) -> [u8; 5] {
+    match ch {
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b001, 0b001, 0b001],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        'A' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'B' => [0b110, 0b101, 0b110, 0b101, 0b110],
+        'C' => [0b011, 0b100, 0b100, 0b100, 0b011],
+        'D' => [0b110, 0b101, 0b101, 0b101, 0b110],
+        'E' => [0b111, 0b100, 0b111, 0b100, 0b111],
+        'F' => [0b111, 0b100, 0b111, 0b100, 0b100],
+        'X' => [0b101, 0b101, 0b010, 0b101, 0b101],
+        _ => [0; 5],
+    }
+}
+
+struct Canvas {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+impl Canvas {
+    fn new(width: u64, height: u64) -> Result<Self, String> {
+        let too_large = || format!("a {}x{} pixel image is too large to render", width, height);
+        let (width, height) = (u32::try_from(width).map_err(|_| too_large())?, u32::try_from(height).map_err(|_| too_large())?);
+        let len = (width as usize).checked_mul(height as usize).and_then(|n| n.checked_mul(3)).ok_or_else(too_large)?;
+        Ok(Canvas { width, height, pixels: vec![255; len] })
+    }
+
+    fn put(&mut self, x: i64, y: i64, (r, g, b): Rgb) {
+        if x < 0 || y < 0 || x >= self.width as i64 || y >= self.height as i64 {
+            return;
+        }
+        let i = (y as usize * self.width as usize + x as usize) * 3;
+        self.pixels[i..i + 3].copy_from_slice(&[r, g, b]);
+    }
+
+    fn fill_rect(&mut self, x: f64, y: f64, w: f64, h: f64, color: Rgb) {
+        for py in y.round() as i64..(y + h).round() as i64 {
+            for px in x.round() as i64..(x + w).round() as i64 {
+                self.put(px, py, color);
+            }
+        }
+    }
+
+    // Segment épais : tous les pixels à moins de `width / 2` du segment
+    fn line(&mut self, (x0, y0): (f64, f64), (x1, y1): (f64, f64), width: f64, color: Rgb) {
+        let half = width / 2.0;
+        let (dx, dy) = (x1 - x0, y1 - y0);
+        let len2 = dx * dx + dy * dy;
+        for py in (y0.min(y1) - half).floor() as i64..=(y0.max(y1) + half).ceil() as i64 {
+            for px in (x0.min(x1) - half).floor() as i64..=(x0.max(x1) + half).ceil() as i64 {
+                let (x, y) = (px as f64 + 0.5, py as f64 + 0.5);
+                let t = if len2 == 0.0 { 0.0 } else { (((x - x0) * dx + (y - y0) * dy) / len2).clamp(0.0, 1.0) };
+                let (ex, ey) = (x - (x0 + t * dx), y - (y0 + t * dy));
+                if ex * ex + ey * ey <= half * half {
+                    self.put(px, py, color);
+                }
+            }
+        }
+    }
+
+    fn disc(&mut self, center: (f64, f64), radius: f64, color: Rgb) {
+        self.line(center, center, radius * 2.0, color);
+    }
+
+    fn text(&mut self, (cx, cy): (f64, f64), text: &str, scale: i64, color: Rgb) {
+        let width = text.len() as i64 * 4 * scale - scale;
+        let left = cx.round() as i64 - width / 2;
+        let top = cy.round() as i64 - 5 * scale / 2;
+        for (i, ch) in text.chars().enumerate() {
+            for (row, bits) in glyph(ch).iter().enumerate() {
+                for col in 0..3 {
+                    if bits & (0b100 >> col) == 0 {
+                        continue;
+                    }
+                    let x = left + (i as i64 * 4 + col) * scale;
+                    let y = top + row as i64 * scale;
+                    for dy in 0..scale {
+                        for dx in 0..scale {
+                            self.put(x + dx, y + dy, color);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Plus grand côté de cellule, au plus `cell_size`, qui garde l'image sous MAX_PNG_PIXELS
+fn png_layout(grid: &Grid, options: &ImageOptions) -> Result<Layout, String> {
+    let pixels = |layout: &Layout| layout.width.saturating_mul(layout.height);
+    let mut layout = Layout::new(grid, options.topology, options.cell_size);
+    let mut cell = layout.cell as u32;
+    while cell > MIN_CELL_SIZE && pixels(&layout) > MAX_PNG_PIXELS {
+        cell -= 1;
+        layout = Layout::new(grid, options.topology, cell);
+    }
+    if pixels(&layout) > MAX_PNG_PIXELS {
+        return Err(format!("the {}x{} grid is too large to render as PNG, use an .svg file", grid.rows(), grid.cols()));
+    }
+    Ok(layout)
+}
+
+pub fn render_png(grid: &Grid, overlays: &[Overlay], start: Coord, end: Coord, options: &ImageOptions) -> Result<Vec<u8>, String> {
+    let layout = png_layout(grid, options)?;
+    let cell = layout.cell;
+    let mut canvas = Canvas::new(layout.width, layout.height)?;
+
+    for r in 0..grid.rows() {
+        for c in 0..grid.cols() {
+            let (x, y) = layout.origin((r, c));
+            canvas.fill_rect(x, y, cell, cell, cell_rgb(grid.intensity((r, c))));
+        }
+    }
+
+    if options.labels {
+        let scale = (cell as i64 / 12).max(1);
+        for r in 0..grid.rows() {
+            for c in 0..grid.cols() {
+                let color = label_rgb(cell_rgb(grid.intensity((r, c))));
+                canvas.text(layout.center((r, c)), &grid.token((r, c)), scale, color);
+            }
+        }
+    }
+
+    for overlay in overlays {
+        for pair in overlay.path.windows(2) {
+            canvas.line(layout.center(pair[0]), layout.center(pair[1]), cell / 6.0, overlay.color);
+        }
+    }
+
+    for (pos, color) in [(start, START_RGB), (end, END_RGB)] {
+        canvas.disc(layout.center(pos), cell / 4.0 + cell / 32.0, OUTLINE_RGB);
+        canvas.disc(layout.center(pos), cell / 4.0 - cell / 32.0, color);
+    }
+
+    let mut out = Vec::new();
+    let mut encoder = png::Encoder::new(&mut out, canvas.width, canvas.height);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(|e| e.to_string())?;
+    writer.write_image_data(&canvas.pixels).map_err(|e| e.to_string())?;
+    writer.finish().map_err(|e| e.to_string())?;
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(rows: usize, cols: usize) -> Grid {
+        Grid::new(rows, cols, (0..rows * cols).map(|i| (i % 7 != 3).then_some(i as i64 % 256)).collect())
+    }
+
+    fn options(topology: Topology, cell_size: u32) -> ImageOptions {
+        ImageOptions { topology, cell_size, labels: true }
+    }
+
+    #[test]
+    fn png_has_the_grid_dimensions() {
+        let grid = grid(3, 5);
+        let path = [(0, 0), (0, 1), (1, 1), (2, 1), (2, 2), (2, 3), (2, 4)];
+        let overlays = [Overlay { path: &path, color: MIN_PATH_RGB }];
+        for (topology, width) in [(Topology::Four, 5 * 16), (Topology::Hex, 5 * 16 + 8)] {
+            let png = render_png(&grid, &overlays, (0, 0), (2, 4), &options(topology, 16)).unwrap();
+            assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+            let reader = png::Decoder::new(png.as_slice()).read_info().unwrap();
+            let info = reader.info();
+            assert_eq!((info.width, info.height, info.color_type), (width, 3 * 16, png::ColorType::Rgb));
+        }
+    }
+
+    #[test]
+    fn large_grids_are_drawn_with_smaller_cells() {
+        let layout = png_layout(&grid(1200, 1200), &options(Topology::Four, 32)).unwrap();
+        assert!(layout.width * layout.height <= MAX_PNG_PIXELS);
+        assert_eq!(layout.cell, 6.0);
+        assert!(png_layout(&grid(3000, 3000), &options(Topology::Four, 32)).is_err());
+        assert!(Canvas::new(u32::MAX as u64 + 1, 1).is_err());
+    }
+
+    #[test]
+    fn svg_draws_cells_paths_and_endpoints() {
+        let grid = grid(2, 3);
+        let path = [(0, 0), (0, 1), (1, 1), (1, 2)];
+        let overlays = [Overlay { path: &path, color: MAX_PATH_RGB }, Overlay { path: &[], color: MIN_PATH_RGB }];
+        let svg = render_svg(&grid, &overlays, (0, 0), (1, 2), &options(Topology::Four, 10));
+        assert!(svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="30" height="20" viewBox="0 0 30 20">"#));
+        assert!(svg.ends_with("</svg>\n"));
+        assert_eq!(svg.matches("<rect ").count(), 6);
+        assert_eq!(svg.matches("<text ").count(), 6);
+        assert_eq!(svg.matches("<circle ").count(), 2);
+        // Le chemin vide n'est pas dessiné
+        assert_eq!(svg.matches("<polyline ").count(), 1);
+        assert!(svg.contains(r##"<polyline points="5,5 15,5 15,15 25,15" fill="none" stroke="#dc1414""##));
+    }
+}
@@ -0,0 +1,315 @@
+use crate::grid::{parse_map, Grid, ParseError, ParseOptions};
+use crate::topology::Movement;
+use crate::Coord;
+use clap::ValueEnum;
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+/// Ligne séparant deux couches dans le format texte
+pub const LAYER_SEPARATOR: &str = "---";
+
+/// Position `(couche, (ligne, colonne))` dans une carte à plusieurs couches
+pub type LayerCoord = (usize, Coord);
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LayerMode {
+    /// Layers are floors, only connectors (`@` lines) lead from one to another
+    Floors,
+    /// Layer i gives the costs at steps i, i + n, i + 2n... and waiting in place is allowed
+    Time,
+}
+
+/// Passage d'une couche à une autre (escalier, ascenseur), son coût est celui de la cellule d'arrivée
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Connector {
+    pub from: LayerCoord,
+    pub to: LayerCoord,
+}
+
+/// Couches de mêmes dimensions et connecteurs entre elles
+#[derive(Clone, Debug)]
+pub struct LayeredGrid {
+    pub layers: Vec<Grid>,
+    pub connectors: Vec<Connector>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LayeredPath {
+    pub cost: i64,
+    pub path: Vec<LayerCoord>,
+    pub nodes_expanded: u64,
+}
+
+/// Vrai si le texte contient au moins un séparateur de couches
+pub fn is_layered(map_data: &str) -> bool {
+    map_data.lines().any(|line| line.trim() == LAYER_SEPARATOR)
+}
+
+// `L:R,C`
+fn parse_layer_coord(text: &str) -> Option<LayerCoord> {
+    let (layer, pos) = text.split_once(':')?;
+    let (r, c) = pos.split_once(',')?;
+    Some((layer.trim().parse().ok()?, (r.trim().parse().ok()?, c.trim().parse().ok()?)))
+}
+
+// `@ L:R,C <-> L:R,C` dans les deux sens, `@ L:R,C -> L:R,C` dans un seul
+fn parse_connector(line: &str) -> Option<Vec<Connector>> {
+    let body = line.trim().strip_prefix('@')?;
+    let (from, to, both) = match body.split_once("<->") {
+        Some((from, to)) => (from, to, true),
+        None => body.split_once("->").map(|(from, to)| (from, to, false))?,
+    };
+    let (from, to) = (parse_layer_coord(from)?, parse_layer_coord(to)?);
+    let mut connectors = vec![Connector { from, to }];
+    if both {
+        connectors.push(Connector { from: to, to: from });
+    }
+    Some(connectors)
+}
+
+/// Lit une carte texte à plusieurs couches séparées par `---`. Chaque couche suit le format de `parse_map` ;
+/// les lignes `@ L:R,C <-> L:R,C` (ou `->` pour un sens unique) déclarent des connecteurs entre couches.
+pub fn parse_layers(map_data: &str, options: ParseOptions) -> Result<LayeredGrid, ParseError> {
+    // Chaque couche garde les numéros de ligne du fichier complet pour ses messages d'erreur
+    let mut sections: Vec<(Vec<usize>, String)> = vec![(Vec::new(), String::new())];
+    let mut connectors = Vec::new();
+    for (line_index, line) in map_data.lines().enumerate() {
+        if line.trim() == LAYER_SEPARATOR {
+            sections.push((Vec::new(), String::new()));
+        } else if line.trim_start().starts_with('@') {
+            let parsed = parse_connector(line)
+                .ok_or_else(|| ParseError::Malformed(format!("line {}: expected @ L:R,C <-> L:R,C but got '{}'", line_index + 1, line.trim())))?;
+            connectors.extend(parsed.into_iter().map(|connector| (line_index + 1, connector)));
+        } else {
+            let (lines, text) = sections.last_mut().unwrap();
+            lines.push(line_index + 1);
+            text.push_str(line);
+            text.push('\n');
+        }
+    }
+
+    let mut layers: Vec<Grid> = Vec::with_capacity(sections.len());
+    for (lines, text) in &sections {
+        let layer = parse_map(text, options).map_err(|e| match e {
+            ParseError::Invalid(mut issues) => {
+                issues.iter_mut().for_each(|issue| issue.line = lines[issue.line - 1]);
+                ParseError::Invalid(issues)
+            }
+            ParseError::Empty => ParseError::Malformed(format!("layer {} contains no cells", layers.len())),
+            other => other,
+        })?;
+        if let Some(first) = layers.first() {
+            if (layer.rows(), layer.cols()) != (first.rows(), first.cols()) {
+                return Err(ParseError::Malformed(format!("layer {} is {}x{}, expected {}x{} like layer 0",
+                                                         layers.len(), layer.rows(), layer.cols(), first.rows(), first.cols())));
+            }
+        }
+        layers.push(layer);
+    }
+
+    // Toutes les couches partagent la largeur de cellule la plus grande
+    let width = layers.iter().map(|layer| layer.width()).max().unwrap_or_default();
+    let layers: Vec<Grid> = layers.into_iter().map(|layer| layer.with_cell_type(width, options.signed)).collect();
+    for &(line, Connector { from, to }) in &connectors {
+        for (layer, pos) in [from, to] {
+            if layer >= layers.len() || !layers[layer].contains(pos) {
+                return Err(ParseError::Malformed(format!("line {}: connector end {}:{},{} is outside the map", line, layer, pos.0, pos.1)));
+            }
+            if layers[layer].is_wall(pos) {
+                return Err(ParseError::Malformed(format!("line {}: connector end {}:{},{} is a wall", line, layer, pos.0, pos.1)));
+            }
+        }
+    }
+    Ok(LayeredGrid { layers, connectors: connectors.into_iter().map(|(_, connector)| connector).collect() })
+}
+
+impl LayeredGrid {
+    pub fn rows(&self) -> usize {
+        self.layers[0].rows()
+    }
+
+    pub fn cols(&self) -> usize {
+        self.layers[0].cols()
+    }
+
+    pub fn has_negative(&self) -> bool {
+        self.layers.iter().any(|layer| layer.has_negative())
+    }
+
+    /// Coût de chaque pas d'un chemin trouvé par `layered_dijkstra`, le départ compte pour 0
+    pub fn step_costs(&self, path: &[LayerCoord], movement: Movement, mode: LayerMode) -> Vec<i64> {
+        std::iter::once(0)
+            .chain(path.windows(2).map(|pair| {
+                self.successors(pair[0], movement, mode).into_iter()
+                    .filter(|&(next, _)| next == pair[1])
+                    .map(|(_, cost)| cost)
+                    .min()
+                    .unwrap_or(0)
+            }))
+            .collect()
+    }
+
+    // Successeurs d'un état avec le coût du pas, selon le sens donné aux couches
+    fn successors(&self, (layer, pos): LayerCoord, movement: Movement, mode: LayerMode) -> Vec<(LayerCoord, i64)> {
+        match mode {
+            LayerMode::Floors => {
+                let grid = &self.layers[layer];
+                let mut next: Vec<(LayerCoord, i64)> = movement.neighbors(pos, grid)
+                    .map(|next| ((layer, next), movement.step_cost(grid, pos, next)))
+                    .collect();
+                for connector in self.connectors.iter().filter(|connector| connector.from == (layer, pos)) {
+                    let (to_layer, to) = connector.to;
+                    next.push((connector.to, self.layers[to_layer].cell(to).unwrap_or(0)));
+                }
+                next
+            }
+            LayerMode::Time => {
+                // Chaque pas, attente comprise, fait passer à la tranche suivante
+                let slice = (layer + 1) % self.layers.len();
+                let grid = &self.layers[slice];
+                let mut next: Vec<(LayerCoord, i64)> = movement.neighbors(pos, grid)
+                    .map(|next| ((slice, next), movement.step_cost(grid, pos, next)))
+                    .collect();
+                if let Some(cost) = grid.cell(pos) {
+                    next.push(((slice, pos), cost));
+                }
+                next
+            }
+        }
+    }
+}
+
+/// Chemin minimal dans l'espace des états (couche, cellule), coûts positifs uniquement.
+/// En mode `Floors` il faut atteindre `end` sur sa couche ; en mode `Time` la couche de départ est
+/// la tranche de temps initiale et `end` peut être atteint à n'importe quel instant.
+pub fn layered_dijkstra(grid: &LayeredGrid, start: LayerCoord, end: LayerCoord, movement: Movement, mode: LayerMode) -> Option<LayeredPath> {
+    let cells = grid.rows() * grid.cols();
+    let index = |(layer, (r, c)): LayerCoord| layer * cells + r * grid.cols() + c;
+    let coord = |i: usize| (i / cells, ((i % cells) / grid.cols(), i % grid.cols()));
+    let is_end = |state: LayerCoord| match mode {
+        LayerMode::Floors => state == end,
+        LayerMode::Time => state.1 == end.1,
+    };
+
+    let mut dist = vec![i64::MAX; cells * grid.layers.len()];
+    let mut predecessors = vec![usize::MAX; dist.len()];
+    let mut heap = BinaryHeap::new();
+    let mut nodes_expanded = 0;
+    if grid.layers[start.0].is_wall(start.1) {
+        return None;
+    }
+    dist[index(start)] = 0;
+    heap.push(Reverse((0i64, index(start))));
+
+    while let Some(Reverse((cost, current))) = heap.pop() {
+        if cost > dist[current] {
+            continue;
+        }
+        let state = coord(current);
+        if is_end(state) {
+            let mut path = vec![state];
+            let mut curr = current;
+            while curr != index(start) {
+                curr = predecessors[curr];
+                path.push(coord(curr));
+            }
+            path.reverse();
+            return Some(LayeredPath { cost, path, nodes_expanded });
+        }
+        nodes_expanded += 1;
+
+        for (next, step) in grid.successors(state, movement, mode) {
+            let Some(total) = cost.checked_add(step) else { continue };
+            let i = index(next);
+            if total < dist[i] {
+                dist[i] = total;
+                predecessors[i] = current;
+                heap.push(Reverse((total, i)));
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::dijkstra;
+    use crate::solver::testing::{checked_cost, tiny_grid, MOVEMENTS};
+
+    const FOUR: Movement = MOVEMENTS[0];
+
+    fn layers(text: &str) -> LayeredGrid {
+        parse_layers(text, ParseOptions::default()).unwrap()
+    }
+
+    #[test]
+    fn waiting_in_place_beats_moving_through_a_costly_slice() {
+        let grid = layers("01 01 01\n---\n01 09 01\n");
+        let found = layered_dijkstra(&grid, (0, (0, 0)), (0, (0, 2)), FOUR, LayerMode::Time).unwrap();
+        // Avancer tout de suite coûte 9 + 1 ; attendre une tranche coûte 1 + 1 + 1
+        assert_eq!(found.cost, 3);
+        assert_eq!(found.path, [(0, (0, 0)), (1, (0, 0)), (0, (0, 1)), (1, (0, 2))]);
+        assert_eq!(grid.step_costs(&found.path, FOUR, LayerMode::Time), [0, 1, 1, 1]);
+
+        // Une attente est impossible sur une cellule murée dans la tranche suivante
+        let grid = layers("01 01 01\n---\nXX 09 01\n");
+        let found = layered_dijkstra(&grid, (0, (0, 0)), (0, (0, 2)), FOUR, LayerMode::Time).unwrap();
+        assert_eq!((found.cost, found.path), (10, vec![(0, (0, 0)), (1, (0, 1)), (0, (0, 2))]));
+    }
+
+    #[test]
+    fn floors_are_only_joined_by_connectors() {
+        let text = "01 XX 01\n---\n02 03 04\n@ 0:0,0 <-> 1:0,0\n";
+        let one_way = layers(&format!("{}@ 0:0,2 -> 1:0,2\n", text));
+        assert_eq!(one_way.connectors.len(), 3);
+        assert_eq!(layered_dijkstra(&one_way, (0, (0, 0)), (0, (0, 2)), FOUR, LayerMode::Floors), None);
+
+        let both_ways = layers(&format!("{}@ 1:0,2 <-> 0:0,2\n", text));
+        let found = layered_dijkstra(&both_ways, (0, (0, 0)), (0, (0, 2)), FOUR, LayerMode::Floors).unwrap();
+        assert_eq!(found.path, [(0, (0, 0)), (1, (0, 0)), (1, (0, 1)), (1, (0, 2)), (0, (0, 2))]);
+        assert_eq!(found.cost, 2 + 3 + 4 + 1);
+        // Sans connecteur sous les pieds, la même cellule de l'autre couche reste hors d'atteinte
+        assert_eq!(layered_dijkstra(&both_ways, (0, (0, 0)), (1, (0, 1)), FOUR, LayerMode::Floors).map(|found| found.cost), Some(5));
+        assert_eq!(layered_dijkstra(&both_ways, (0, (0, 2)), (0, (0, 0)), FOUR, LayerMode::Floors).map(|found| found.cost), Some(4 + 3 + 2 + 1));
+
+        let walled = parse_layers(&format!("{}@ 0:0,1 <-> 1:0,1\n", text), ParseOptions::default()).unwrap_err();
+        assert_eq!(walled.to_string(), "Invalid map format: line 5: connector end 0:0,1 is a wall");
+        let outside = parse_layers(&format!("{}@ 2:0,0 -> 1:0,0\n", text), ParseOptions::default()).unwrap_err();
+        assert_eq!(outside.to_string(), "Invalid map format: line 5: connector end 2:0,0 is outside the map");
+    }
+
+    #[test]
+    fn single_layer_matches_plain_dijkstra() {
+        for movement in MOVEMENTS {
+            for seed in 0..20 {
+                let grid = tiny_grid(5, 6, 0.25, false, seed);
+                let (start, end) = ((0, 0), (4, 5));
+                let expected = dijkstra(&grid, start, end, movement, &mut ()).map(|(cost, _)| cost);
+                let layered = LayeredGrid { layers: vec![grid.clone()], connectors: Vec::new() };
+                for mode in [LayerMode::Floors, LayerMode::Time] {
+                    let found = layered_dijkstra(&layered, (0, start), (0, end), movement, mode);
+                    assert_eq!(found.as_ref().map(|found| found.cost), expected, "{:?} {:?} seed {}", movement, mode, seed);
+                    let Some(found) = found else { continue };
+                    let steps = layered.step_costs(&found.path, movement, mode);
+                    assert_eq!(steps.iter().sum::<i64>(), found.cost);
+                    if mode == LayerMode::Floors {
+                        let cells: Vec<Coord> = found.path.iter().map(|&(_, pos)| pos).collect();
+                        assert_eq!(checked_cost(&grid, &cells, start, end, movement), found.cost);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn layers_must_share_their_size() {
+        let error = parse_layers("01 02\n---\n01\n", ParseOptions::default()).unwrap_err();
+        assert_eq!(error.to_string(), "Invalid map format: layer 1 is 1x1, expected 1x2 like layer 0");
+        let error = parse_layers("01 02\n---\n01 ZZ\n", ParseOptions::default()).unwrap_err();
+        assert_eq!(error.to_string(), "Invalid map format (1 error):\n  line 3, column 4: invalid hex value 'ZZ'");
+        assert!(!is_layered("01 02\n-- \n") && is_layered("01\n --- \n02\n"));
+    }
+}
@@ -0,0 +1,36 @@
+//! Recherche de chemins de coût minimal et maximal dans des grilles hexadécimales.
+//!
+//! Le binaire `hexpath` n'est qu'une interface en ligne de commande autour de ce crate.
+
+pub mod batch;
+pub mod bench;
+pub mod cache;
+pub mod diff;
+pub mod editor;
+pub mod field;
+pub mod format;
+pub mod generate;
+pub mod grid;
+pub mod image;
+pub mod layers;
+pub mod render;
+pub mod report;
+pub mod route;
+pub mod solver;
+pub mod topology;
+pub mod verify;
+
+/// Position `(ligne, colonne)` dans la grille
+pub type Coord = (usize, usize);
+
+pub use format::{decode_map, encode_map, MapFormat};
+pub use generate::{generate_map, generate_maze, Distribution, Maze, Terrain};
+pub use grid::{parse_map, CellWidth, Grid, ParseError, ParseIssue, ParseOptions, WALL_TOKEN};
+pub use layers::{layered_dijkstra, parse_layers, LayerCoord, LayerMode, LayeredGrid};
+pub use route::{plan_route, Leg, Route, MAX_OPTIMIZED_WAYPOINTS};
+pub use solver::{
+    astar, bellman_ford, capped_max_path, dial_dijkstra, dial_max_step, dijkstra, greedy_max_path, k_shortest_paths, max_path_dfs, path_overlap,
+    step_limited_path, AStar, Algorithm, BellmanFord, Dijkstra, GreedyMaxPath, Heuristic, MaxPathDfs, NegativeCycle, PathResult, SearchBudget,
+    SearchObserver, SearchStats, Solver, StepLimited, DIAL_MIN_CELLS, MAX_DIAL_STEP,
+};
+pub use topology::{Movement, Topology};
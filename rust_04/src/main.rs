@@ -1,316 +1,299 @@
-use clap::{Parser, CommandFactory};
+use clap::{Args, CommandFactory, Parser, Subcommand, ValueEnum};
+use hexpath::batch::{collect_inputs, run_batch, BatchOptions, PathSummary};
+use hexpath::cache::{cache_key, Cache};
+use hexpath::diff::{diff_maps, CellChange, DiffOptions, PathComparison};
+use hexpath::field::distance_field;
+use hexpath::layers::is_layered;
+use hexpath::bench::{bench_size, header_line, size_lines, BenchOptions, BenchReport};
+use hexpath::editor::{run_editor, EditorOptions};
+use hexpath::image::{
+    render_png, render_svg, ImageFormat, ImageOptions, Overlay, ALTERNATIVE_RGB, MAX_PATH_RGB, MIN_PATH_RGB,
+};
+use hexpath::render::{
+    cell_label, hex_cost, print_alternatives, print_layered_path, print_path_details, print_route_legs, visualize_map, visualize_paths, visualize_side_by_side, Animator,
+    ALTERNATIVE_ANSI,
+};
+use hexpath::report::{step_costs, write_batch_csv, write_bench_csv, write_diff_csv, write_verify_csv, AlternativeReport, write_csv_report, CostRange, LayeredPathReport, LayeredReport, LegReport, OutputFormat, PathReport, Report, SolverInfo};
+use hexpath::verify::{parse_path, verify_path};
+use hexpath::{
+    capped_max_path, decode_map, encode_map, generate_map, generate_maze, k_shortest_paths, layered_dijkstra, parse_layers, path_overlap, plan_route, Algorithm, Coord, Distribution, Grid, Heuristic, LayerMode, Maze, Movement,
+    MapFormat, ParseOptions, PathResult, Route, SearchBudget, SearchObserver, SearchStats, Solver, StepLimited, Terrain, Topology, MAX_OPTIMIZED_WAYPOINTS,
+};
 use rand::Rng;
-use std::collections::{BinaryHeap, HashMap};
 use std::fs;
-use std::cmp::Ordering;
+use std::path::Path;
+use std::process::ExitCode;
+use std::time::{Duration, Instant};
 
 #[cfg(windows)]
 use std::os::windows::ffi::OsStrExt;
 #[cfg(windows)]
 use std::ffi::OsStr;
 
-type Coord = (usize, usize);
-
-#[derive(Copy, Clone, Eq, PartialEq)]
-struct State {
-    cost: u32,
-    position: Coord,
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Edit a map in a full-screen terminal UI with live min/max paths
+    Edit(EditArgs),
+    /// Time the solvers on generated maps of several sizes
+    Bench(BenchArgs),
+    /// Solve every map of a directory or glob pattern in parallel and print one summary
+    Batch(BatchArgs),
+    /// Check a hand-drawn route and compare its cost with the minimum
+    Verify(VerifyArgs),
+    /// Manage the on-disk cache of min/max results
+    Cache(CacheArgs),
+    /// Compare two versions of a map: changed cells and how the min/max paths moved
+    Diff(DiffArgs),
 }
 
-impl Ord for State {
-    fn cmp(&self, other: &Self) -> Ordering {
-        other.cost.cmp(&self.cost)
-            .then_with(|| self.position.cmp(&other.position))
-    }
+#[derive(Args, Debug)]
+struct CacheArgs {
+    #[command(subcommand)]
+    action: CacheAction,
 }
 
-impl PartialOrd for State {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
+#[derive(Subcommand, Debug)]
+enum CacheAction {
+    /// Delete every cached result. The cache keeps the 1000 most recently used results and drops older ones
+    Clear,
 }
 
-fn parse_map(map_data: &str) -> Option<Vec<Vec<u8>>> {
-    let mut grid = Vec::new();
-    let mut cols = 0;
-    
-    for line in map_data.lines() {
-        let hex_values: Vec<&str> = line.split_whitespace().collect();
-        if hex_values.is_empty() { continue; }
-
-        let row: Vec<u8> = hex_values.iter()
-            .filter_map(|s| u8::from_str_radix(s, 16).ok())
-            .collect();
+/// Voisinage et coût des diagonales, communs aux commandes qui cherchent des chemins
+#[derive(Args, Debug)]
+struct MovementArgs {
+    /// Neighbourhood used by every solver
+    #[arg(long, value_enum, default_value_t = Topology::Four)]
+    topology: Topology,
 
-        if row.len() > 0 {
-            if cols == 0 { cols = row.len(); }
-            if row.len() != cols { return None; }
-            grid.push(row);
-        }
-    }
-    Some(grid)
+    /// Cost multiplier applied to diagonal steps with --topology 8
+    #[arg(long, default_value_t = 1.0, value_name = "FACTOR")]
+    diagonal_cost: f64,
 }
 
-fn generate_map(w: usize, h: usize) -> Vec<Vec<String>> {
-    let mut rng = rand::thread_rng();
-    let mut grid = Vec::with_capacity(h);
-
-    for r in 0..h {
-        let mut row = Vec::with_capacity(w);
-        for c in 0..w {
-            let value = match (r, c) {
-                (0, 0) => 0u8,
-                _ if r == h - 1 && c == w - 1 => 0xFFu8,
-                _ => rng.gen_range(0x01..0xFE),
-            };
-            row.push(format!("{:02X}", value));
+impl MovementArgs {
+    fn movement(&self) -> Result<Movement, String> {
+        if !(self.diagonal_cost.is_finite() && self.diagonal_cost >= 0.0) {
+            return Err("--diagonal-cost must be a non-negative number".to_string());
         }
-        grid.push(row);
+        Ok(Movement { topology: self.topology, diagonal_cost: self.diagonal_cost })
     }
-    grid
 }
 
-fn dijkstra(grid: &Vec<Vec<u8>>, start: Coord, end: Coord) -> Option<(u32, Vec<Coord>)> {
-    let rows = grid.len();
-    let cols = grid[0].len();
-    let mut dist: HashMap<Coord, u32> = HashMap::new();
-    let mut predecessors: HashMap<Coord, Coord> = HashMap::new();
-    let mut heap = BinaryHeap::new();
-
-    dist.insert(start, 0);
-    heap.push(State { cost: 0, position: start });
-
-    let moves = [(0, 1), (0, -1), (1, 0), (-1, 0)];
-
-    while let Some(State { cost, position }) = heap.pop() {
-        if position == end {
-            let total_cost = dist[&end];
-            let mut path = Vec::new();
-            let mut curr = end;
-            
-            while curr != start {
-                path.push(curr);
-                curr = predecessors[&curr];
-            }
-            path.push(start);
-            path.reverse();
-            return Some((total_cost, path));
-        }
+/// Extrémités, voisinage et lecture des cellules, communs aux commandes qui résolvent une carte
+#[derive(Args, Debug)]
+struct MapArgs {
+    /// Start cell (defaults to the top-left corner)
+    #[arg(long, value_name = "R,C", value_parser = parse_coord)]
+    start: Option<Coord>,
 
-        if cost > dist[&position] {
-            continue;
-        }
+    /// End cell (defaults to the bottom-right corner)
+    #[arg(long, value_name = "R,C", value_parser = parse_coord)]
+    end: Option<Coord>,
 
-        for (dr, dc) in moves.iter() {
-            let new_r = (position.0 as isize) + dr;
-            let new_c = (position.1 as isize) + dc;
+    #[command(flatten)]
+    movement: MovementArgs,
 
-            if new_r >= 0 && new_r < rows as isize && new_c >= 0 && new_c < cols as isize {
-                let neighbor_pos = (new_r as usize, new_c as usize);
-                let step_cost = grid[neighbor_pos.0][neighbor_pos.1] as u32;
-                let new_total_cost = cost + step_cost;
+    /// Raw cell value treated as a wall, in addition to `XX` tokens
+    #[arg(long, value_name = "HEX", value_parser = parse_hex_value)]
+    wall_value: Option<u32>,
 
-                let current_dist = dist.get(&neighbor_pos).copied().unwrap_or(u32::MAX);
-                
-                if new_total_cost < current_dist {
-                    dist.insert(neighbor_pos, new_total_cost);
-                    predecessors.insert(neighbor_pos, position);
-                    heap.push(State { cost: new_total_cost, position: neighbor_pos });
-                }
+    /// Read cells as two's complement signed values, negative cells are rewards.
+    /// A step costs the value of the cell it enters, so stepping back and forth between a reward and a
+    /// neighbour worth less than the reward's magnitude is a negative cycle and no minimum path exists:
+    /// keep every reward surrounded by cells costing at least as much
+    #[arg(long)]
+    signed: bool,
+}
+
+impl MapArgs {
+    fn parse_options(&self, strict: bool) -> ParseOptions {
+        ParseOptions { wall_value: self.wall_value, strict, signed: self.signed }
+    }
+
+    // Départ et arrivée, par défaut les coins opposés d'une grille de `rows` x `cols`, vérifiés dans la grille
+    fn endpoints(&self, rows: usize, cols: usize) -> Result<(Coord, Coord), String> {
+        let start = self.start.unwrap_or((0, 0));
+        let end = self.end.unwrap_or((rows - 1, cols - 1));
+        for (r, c) in [start, end] {
+            if r >= rows || c >= cols {
+                return Err(format!("Coordinate ({},{}) is outside the {}x{} grid", r, c, rows, cols));
             }
         }
+        Ok((start, end))
     }
+}
+
+#[derive(Args, Debug)]
+struct EditArgs {
+    /// Map to edit, saved back as hex text (next to it with a .txt extension if it is binary)
+    map_file: String,
+
+    #[command(flatten)]
+    map: MapArgs,
 
-    None
+    /// Node budget for the live maximum path search
+    #[arg(long, default_value_t = 5_000_000)]
+    max_nodes: u64,
+
+    /// Time budget in milliseconds for the live maximum path search
+    #[arg(long, default_value_t = 250, value_name = "MS")]
+    time_limit: u64,
 }
 
-fn max_path_dfs(grid: &Vec<Vec<u8>>, start: Coord, end: Coord) -> Option<(u32, Vec<Coord>)> {
-    let rows = grid.len();
-    let cols = grid[0].len();
-    
-    // Pour les grilles > 6x6, utiliser une heuristique glouton au lieu du DFS exhaustif
-    if rows > 6 || cols > 6 {
-        return greedy_max_path(grid, start, end);
-    }
+#[derive(Args, Debug)]
+struct BenchArgs {
+    /// Side lengths of the square maps to generate
+    #[arg(long, value_delimiter = ',', default_values_t = [8, 64, 512], value_name = "N,N,...")]
+    sizes: Vec<usize>,
 
-    let mut visited = vec![vec![false; cols]; rows];
-    let mut best_cost: Option<u32> = None;
-    let mut best_path: Vec<Coord> = Vec::new();
-    let max_depth = ((rows * cols) / 2) as u32; // Limite réduite
+    /// Maps generated and solved per size
+    #[arg(long, default_value_t = 10)]
+    runs: usize,
 
-    fn dfs(grid: &Vec<Vec<u8>>, pos: Coord, end: Coord,
-           visited: &mut Vec<Vec<bool>>, path: &mut Vec<Coord>,
-           cur_cost: u32, best_cost: &mut Option<u32>, best_path: &mut Vec<Coord>,
-           rows: usize, cols: usize, depth: u32, max_depth: u32) {
-        
-        if depth > max_depth {
-            return;
-        }
+    /// Seed of the first map, run i uses seed + i; a random one is picked and printed otherwise
+    #[arg(long)]
+    seed: Option<u64>,
 
-        if pos == end {
-            if best_cost.is_none() || cur_cost > best_cost.unwrap() {
-                *best_cost = Some(cur_cost);
-                *best_path = path.clone();
-            }
-            return;
-        }
-
-        let moves = [(0isize, 1isize), (0, -1), (1, 0), (-1, 0)];
-
-        for (dr, dc) in moves.iter() {
-            let nr = pos.0 as isize + dr;
-            let nc = pos.1 as isize + dc;
-            if nr >= 0 && nr < rows as isize && nc >= 0 && nc < cols as isize {
-                let nr = nr as usize;
-                let nc = nc as usize;
-                if !visited[nr][nc] {
-                    visited[nr][nc] = true;
-                    path.push((nr, nc));
-                    let step_cost = grid[nr][nc] as u32;
-                    dfs(grid, (nr, nc), end, visited, path, cur_cost + step_cost, best_cost, best_path, rows, cols, depth + 1, max_depth);
-                    path.pop();
-                    visited[nr][nc] = false;
-                }
-            }
-        }
-    }
+    #[command(flatten)]
+    movement: MovementArgs,
 
-    visited[start.0][start.1] = true;
-    let mut path = vec![start];
-    dfs(grid, start, end, &mut visited, &mut path, 0u32, &mut best_cost, &mut best_path, rows, cols, 0, max_depth);
+    /// Probability for each generated cell to be a wall
+    #[arg(long, default_value_t = 0.0, value_name = "RATIO")]
+    wall_density: f64,
 
-    best_cost.map(|c| (c, best_path))
-}
+    /// Value distribution of the generated maps
+    #[arg(long, value_enum, default_value_t = Distribution::Uniform)]
+    distribution: Distribution,
 
-// Heuristique glouton pour trouver un chemin de coût élevé (pas exhaustif)
-fn greedy_max_path(grid: &Vec<Vec<u8>>, start: Coord, end: Coord) -> Option<(u32, Vec<Coord>)> {
-    let rows = grid.len();
-    let cols = grid[0].len();
-    let moves = [(0, 1), (0, -1), (1, 0), (-1, 0)];
-    
-    let mut visited = vec![vec![false; cols]; rows];
-    let mut path = vec![start];
-    let mut cost = 0u32;
-    let mut current = start;
-    
-    visited[start.0][start.1] = true;
-
-    // Explore greedily vers les cellules de plus haute valeur
-    while current != end {
-        let mut best_next = None;
-        let mut best_value = 0u8;
-        
-        for (dr, dc) in moves.iter() {
-            let nr = (current.0 as isize + dr) as usize;
-            let nc = (current.1 as isize + dc) as usize;
-            
-            if nr < rows && nc < cols && !visited[nr][nc] {
-                let cell_value = grid[nr][nc];
-                if cell_value > best_value {
-                    best_value = cell_value;
-                    best_next = Some((nr, nc));
-                }
-            }
-        }
-        
-        match best_next {
-            Some((nr, nc)) => {
-                visited[nr][nc] = true;
-                cost += grid[nr][nc] as u32;
-                path.push((nr, nc));
-                current = (nr, nc);
-            }
-            None => {
-                // Si bloqué, chercher n'importe quel chemin non visité
-                let mut found = false;
-                for (dr, dc) in moves.iter() {
-                    let nr = (current.0 as isize + dr) as usize;
-                    let nc = (current.1 as isize + dc) as usize;
-                    
-                    if nr < rows && nc < cols && !visited[nr][nc] {
-                        visited[nr][nc] = true;
-                        cost += grid[nr][nc] as u32;
-                        path.push((nr, nc));
-                        current = (nr, nc);
-                        found = true;
-                        break;
-                    }
-                }
-                if !found { break; }
-            }
-        }
-    }
+    /// Node budget for each exact maximum path search
+    #[arg(long, default_value_t = 5_000_000)]
+    max_nodes: u64,
 
-    if current == end {
-        Some((cost, path))
-    } else {
-        None
-    }
+    /// Time budget in milliseconds for each exact maximum path search
+    #[arg(long, default_value_t = 1000, value_name = "MS")]
+    time_limit: u64,
+
+    /// Output format, json and csv print nothing but the document on stdout
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    format: OutputFormat,
 }
 
-fn hex_to_rainbow_ansi(value: u8) -> String {
-    let color_index = 16 + (value as f32 / 255.0 * 215.0).round() as u8;
-    format!("\x1b[38;5;{}m", color_index)
+#[derive(Args, Debug)]
+struct BatchArgs {
+    /// Directories (maps with a .txt, .hex, .raw, .bin, .pgm or .png extension), glob patterns or files
+    #[arg(required = true)]
+    inputs: Vec<String>,
+
+    /// Worker threads, all cores by default
+    #[arg(long, value_name = "N")]
+    jobs: Option<usize>,
+
+    #[arg(long, value_enum, default_value_t = Algorithm::Dijkstra)]
+    algorithm: Algorithm,
+
+    #[arg(long, value_enum, default_value_t = Heuristic::Manhattan)]
+    heuristic: Heuristic,
+
+    /// Also search the maximum cost path of every map
+    #[arg(long)]
+    both: bool,
+
+    /// Node budget for each exact maximum path search
+    #[arg(long, default_value_t = 5_000_000)]
+    max_nodes: u64,
+
+    /// Time budget in milliseconds for each exact maximum path search
+    #[arg(long, value_name = "MS")]
+    time_limit: Option<u64>,
+
+    #[command(flatten)]
+    map: MapArgs,
+
+    /// Reject comments, blank lines, 0x prefixes and any token that is not 2, 4 or 8 uppercase hex digits
+    #[arg(long)]
+    strict: bool,
+
+    /// Summary format, text prints one line per map
+    #[arg(long, value_enum, default_value_t = OutputFormat::Json)]
+    format: OutputFormat,
 }
 
-fn visualize_map(grid_str: &Vec<Vec<String>>, path: Option<&Vec<Coord>>, path_color: &str) {
-    let path_set = path.map(|p| p.iter().collect::<std::collections::HashSet<_>>()).unwrap_or_default();
-    
-    for (r, row) in grid_str.iter().enumerate() {
-        for (c, hex_val) in row.iter().enumerate() {
-            let value = u8::from_str_radix(hex_val, 16).unwrap_or(0);
-            let color = hex_to_rainbow_ansi(value);
-            
-            let text_color = if !path_set.is_empty() && path_set.contains(&(r, c)) {
-                path_color
-            } else {
-                "\x1b[0m"
-            };
+#[derive(Args, Debug)]
+struct VerifyArgs {
+    /// Map the route runs on
+    map_file: String,
 
-            print!("{}{}{} ", color, text_color, hex_val);
-        }
-        println!("\x1b[0m");
-    }
+    /// Route to check, as printed by hexpath: "(0,0)->(0,1)->..."
+    #[arg(long, value_name = "ROUTE", required_unless_present = "path_file", conflicts_with = "path_file")]
+    path: Option<String>,
+
+    /// File holding the route, either the bare path or a full hexpath path report
+    #[arg(long, value_name = "FILE")]
+    path_file: Option<String>,
+
+    #[command(flatten)]
+    map: MapArgs,
+
+    /// Algorithm computing the optimum, negative costs always use bellman-ford
+    #[arg(long, value_enum, default_value_t = Algorithm::Dijkstra)]
+    algorithm: Algorithm,
+
+    #[arg(long, value_enum, default_value_t = Heuristic::Manhattan)]
+    heuristic: Heuristic,
+
+    /// Output format, json and csv print nothing but the document on stdout
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    format: OutputFormat,
 }
 
-fn print_path_details(name: &str, cost: u32, path: &Vec<Coord>, grid_u8: &Vec<Vec<u8>>) {
-    println!("\n{} COST PATH (shown in {}):", name, if name == "MINIMUM" { "white" } else { "red" });
-    println!("==========================");
-    println!("Total cost: 0x{:X} ({} decimal)", cost, cost);
-    println!("Path length: {} steps", path.len());
-    
-    print!("Path:\n");
-    for (i, &(r, c)) in path.iter().enumerate() {
-        print!("({},{})", r, c);
-        if i < path.len() - 1 {
-            print!("->");
-        }
-        if (i + 1) % 6 == 0 {
-            print!("\n");
-        }
-    }
-    println!();
-    
-    println!("\nStep-by-step costs:");
-    println!("Start 0x{:02X} ({},{})", grid_u8[path[0].0][path[0].1], path[0].0, path[0].1);
-    let mut _current_cost = 0;
-    for i in 1..path.len() {
-        let curr = path[i];
-        let step_cost = grid_u8[curr.0][curr.1] as u32;
-        _current_cost += step_cost;
-        println!("-> 0x{:02X} ({},{}) +{}", grid_u8[curr.0][curr.1], curr.0, curr.1, step_cost);
-    }
-    println!("Total: 0x{:X} ({})", cost, cost);
+#[derive(Args, Debug)]
+struct DiffArgs {
+    /// Map before the update
+    old_file: String,
+
+    /// Map after the update, with the same dimensions
+    new_file: String,
+
+    #[command(flatten)]
+    map: MapArgs,
+
+    /// Algorithm of the minimum path search, negative costs always use bellman-ford
+    #[arg(long, value_enum, default_value_t = Algorithm::Dijkstra)]
+    algorithm: Algorithm,
+
+    #[arg(long, value_enum, default_value_t = Heuristic::Manhattan)]
+    heuristic: Heuristic,
+
+    /// Also compare the maximum cost paths
+    #[arg(long)]
+    both: bool,
+
+    /// Node budget for each exact maximum path search
+    #[arg(long, default_value_t = 5_000_000)]
+    max_nodes: u64,
+
+    /// Time budget in milliseconds for each exact maximum path search
+    #[arg(long, value_name = "MS")]
+    time_limit: Option<u64>,
+
+    /// Output format, csv lists the changed cells
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    format: OutputFormat,
 }
 
 #[derive(Parser, Debug)]
 #[clap(name = "hexpath", version = "1.0", about = "Find min/max cost paths in hexadecimal grid using Dijkstra")]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     #[arg(long)]
     generate: Option<String>,
 
+    /// Map to load: hex text, raw bytes with a width header, PGM or grayscale PNG
     map_file: Option<String>,
 
+    /// Write the map to FILE, as raw bytes (.raw/.bin), PGM (.pgm), PNG (.png) or hex text otherwise
     #[arg(long, value_name = "FILE")]
     output: Option<String>,
 
@@ -320,8 +303,151 @@ struct Cli {
     #[arg(long)]
     both: bool,
 
+    /// Write the distance from the start to every cell to FILE as hex text, instead of solving a path
+    #[arg(long, value_name = "FILE")]
+    distance_field: Option<String>,
+
+    /// With --distance-field, compute the cost of reaching the end from every cell instead
+    #[arg(long, requires = "distance_field")]
+    from_end: bool,
+
+    /// With --distance-field, write the next step of every cell toward the field source to FILE:
+    /// 00 at the source, 01-08 for N, NE, E, SE, S, SW, W, NW
+    #[arg(long, value_name = "FILE", requires = "distance_field")]
+    directions: Option<String>,
+
+    /// With --distance-field, print the field as a heatmap (00 at the source, FF at the farthest cell)
+    #[arg(long, requires = "distance_field")]
+    heatmap: bool,
+
+    /// Always recompute the min/max paths instead of reading or writing the result cache
+    #[arg(long)]
+    no_cache: bool,
+
+    /// Animate the minimum path search in the terminal
     #[arg(long)]
     animate: bool,
+
+    /// List the N cheapest loopless paths (Yen's algorithm)
+    #[arg(long, value_name = "N")]
+    k: Option<usize>,
+
+    /// Draw the grid and the solved paths to an SVG or PNG image
+    #[arg(long, value_name = "FILE")]
+    render: Option<String>,
+
+    /// Print each cell's hex value on the rendered image
+    #[arg(long)]
+    labels: bool,
+
+    /// Side of a cell in pixels on the rendered image; PNG images of large grids use smaller cells to stay within 8192x8192 pixels
+    #[arg(long, default_value_t = 32, value_name = "PX")]
+    cell_size: u32,
+
+    /// Delay between animation frames in milliseconds
+    #[arg(long, default_value_t = 40, value_name = "MS")]
+    frame_delay: u64,
+
+    #[arg(long, value_enum, default_value_t = Algorithm::Dijkstra)]
+    algorithm: Algorithm,
+
+    #[arg(long, value_enum, default_value_t = Heuristic::Manhattan)]
+    heuristic: Heuristic,
+
+    /// Node budget for the exact maximum path search
+    #[arg(long, default_value_t = 5_000_000)]
+    max_nodes: u64,
+
+    /// Time budget in milliseconds for the exact maximum path search
+    #[arg(long, value_name = "MS")]
+    time_limit: Option<u64>,
+
+    #[command(flatten)]
+    map: MapArgs,
+
+    /// Waypoint the minimum path must visit, can be repeated
+    #[arg(long, value_name = "R,C", value_parser = parse_coord)]
+    via: Vec<Coord>,
+
+    /// Visit the waypoints in the cheapest order instead of the given one
+    #[arg(long)]
+    optimize_order: bool,
+
+    /// Find the cheapest path that takes at most N moves
+    #[arg(long, value_name = "N")]
+    max_steps: Option<usize>,
+
+    /// Find the maximum cost path whose total stays at or under COST (decimal, or hex with 0x)
+    #[arg(long, value_name = "COST", value_parser = parse_cost)]
+    budget: Option<i64>,
+
+    /// With --budget, tell whether any path costs between COST and the budget
+    #[arg(long, value_name = "COST", value_parser = parse_cost, requires = "budget")]
+    min_cost: Option<i64>,
+
+    /// How the `---` separated layers of a text map are linked
+    #[arg(long, value_enum, default_value_t = LayerMode::Floors)]
+    layer_mode: LayerMode,
+
+    /// Layer of the start cell on a layered map, the first time slice with --layer-mode time
+    #[arg(long, default_value_t = 0, value_name = "N")]
+    start_layer: usize,
+
+    /// Layer of the end cell on a layered map (defaults to the last one), unused with --layer-mode time
+    #[arg(long, value_name = "N")]
+    end_layer: Option<usize>,
+
+    /// Probability for each generated cell to be a wall
+    #[arg(long, default_value_t = 0.0, value_name = "RATIO")]
+    wall_density: f64,
+
+    /// Generate a 4-neighbour maze of XX walls instead of random costs
+    #[arg(long, value_enum, requires = "generate", conflicts_with = "wall_density")]
+    maze: Option<Maze>,
+
+    /// Probability for each remaining maze wall between two rooms to be removed, 0 keeps a single path between any two open cells, start, end and --via stops included
+    #[arg(long, default_value_t = 0.0, value_name = "RATIO", requires = "maze")]
+    maze_loops: f64,
+
+    /// Give open maze cells random costs from --distribution instead of 01
+    #[arg(long, requires = "maze")]
+    maze_costs: bool,
+
+    /// Reject comments, blank lines, 0x prefixes and any token that is not 2, 4 or 8 uppercase hex digits
+    #[arg(long)]
+    strict: bool,
+
+    /// Output format, json and csv print nothing but the document on stdout
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    format: OutputFormat,
+
+    /// Seed for --generate, a random one is picked and printed otherwise
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// Value distribution for --generate
+    #[arg(long, value_enum, default_value_t = Distribution::Uniform)]
+    distribution: Distribution,
+
+    /// Mean cell value for the normal distribution
+    #[arg(long, default_value_t = 127.0)]
+    mean: f64,
+
+    /// Standard deviation for the normal and clustered distributions
+    #[arg(long, default_value_t = 40.0)]
+    stddev: f64,
+
+    /// Feature size in cells for the perlin distribution
+    #[arg(long, default_value_t = 8.0)]
+    scale: f64,
+
+    /// Noise octaves for the perlin distribution
+    #[arg(long, default_value_t = 4)]
+    octaves: u32,
+
+    /// Number of regions for the clustered distribution
+    #[arg(long, default_value_t = 6)]
+    clusters: usize,
 }
 
 #[cfg(windows)]
@@ -345,96 +471,851 @@ fn enable_ansi_support() {
     // Pas nécessaire sur Linux/Mac
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
+fn main() -> ExitCode {
     enable_ansi_support(); // Activer ANSI avant tout affichage
-    
-    let args = Cli::parse();
-    let mut map_data_str = String::new();
-    let mut grid_str_vec: Option<Vec<Vec<String>>> = None;
+
+    // Affichage avec `Display`, pour garder les diagnostics de lecture d'une carte sur plusieurs lignes
+    match run(Cli::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: Cli) -> Result<(), Box<dyn std::error::Error>> {
+    match &args.command {
+        Some(Command::Edit(edit)) => return run_edit(edit),
+        Some(Command::Bench(bench)) => return run_bench(bench),
+        Some(Command::Batch(batch)) => return run_batch_command(batch),
+        Some(Command::Verify(verify)) => return run_verify(verify),
+        Some(Command::Cache(cache)) => return run_cache(cache),
+        Some(Command::Diff(diff)) => return run_diff(diff),
+        None => {}
+    }
+    let text = args.format == OutputFormat::Text;
+    let mut generated: Option<Grid> = None;
+    let mut map_data = Vec::new();
+    let mut map_format = MapFormat::Text;
+    let mut generated_seed = None;
 
     if let Some(ref dims) = args.generate {
         let parts: Vec<&str> = dims.split('x').collect();
         if parts.len() == 2 {
             let w = parts[0].parse::<usize>().unwrap_or(8);
             let h = parts[1].parse::<usize>().unwrap_or(8);
-            grid_str_vec = Some(generate_map(w, h));
-            println!("Generating {}x{} hexadecimal grid...", w, h);
+            if w == 0 || h == 0 {
+                return Err("--generate dimensions must be at least 1x1".into());
+            }
+            if !(0.0..=1.0).contains(&args.wall_density) {
+                return Err("--wall-density must be between 0 and 1".into());
+            }
+            let mut stops = vec![args.map.start.unwrap_or((0, 0))];
+            stops.extend_from_slice(&args.via);
+            stops.push(args.map.end.unwrap_or((h - 1, w - 1)));
+            if !(args.stddev.is_finite() && args.stddev >= 0.0) {
+                return Err("--stddev must be a non-negative number".into());
+            }
+            let terrain = Terrain {
+                distribution: args.distribution,
+                mean: args.mean,
+                stddev: args.stddev,
+                scale: args.scale,
+                octaves: args.octaves,
+                clusters: args.clusters,
+            };
+            let seed = args.seed.unwrap_or_else(|| rand::thread_rng().gen());
+            generated_seed = Some(seed);
+            match args.maze {
+                Some(maze) => {
+                    if !(0.0..=1.0).contains(&args.maze_loops) {
+                        return Err("--maze-loops must be between 0 and 1".into());
+                    }
+                    generated = Some(generate_maze(w, h, maze, args.maze_loops, &stops, args.maze_costs.then_some(&terrain), seed));
+                    if text {
+                        println!("Generating {}x{} {} maze (seed {})...", w, h, maze.to_possible_value().map_or_else(String::new, |v| v.get_name().to_string()), seed);
+                    }
+                }
+                None => {
+                    generated = Some(generate_map(w, h, args.wall_density, &stops, &terrain, seed));
+                    if text {
+                        println!("Generating {}x{} hexadecimal grid (seed {})...", w, h, seed);
+                    }
+                }
+            }
         }
     } else if let Some(filename) = &args.map_file {
-        map_data_str = fs::read_to_string(filename)?;
-        println!("Analyzing hexadecimal grid...");
+        map_data = fs::read(filename)?;
+        map_format = MapFormat::detect(Path::new(filename), &map_data);
+        if text {
+            println!("Analyzing hexadecimal grid...");
+        }
     } else {
         Cli::command().print_help()?;
         return Ok(());
     }
 
-    let grid_str_to_process = grid_str_vec.as_ref().map(|g| 
-        g.iter().map(|r| r.join(" ")).collect::<Vec<String>>().join("\n")
-    ).unwrap_or(map_data_str.clone());
-
-    let grid_u8 = parse_map(&grid_str_to_process).ok_or("Invalid map format")?;
-    
-    if grid_str_vec.is_none() {
-        grid_str_vec = Some(grid_u8.iter().map(|r| 
-            r.iter().map(|&v| format!("{:02X}", v)).collect()
-        ).collect());
+    // Une carte texte à plusieurs couches suit son propre chemin
+    if map_format == MapFormat::Text {
+        if let Some(map_text) = std::str::from_utf8(&map_data).ok().filter(|map_text| is_layered(map_text)) {
+            return run_layered(&args, map_text);
+        }
     }
 
-    let rows = grid_u8.len();
-    let cols = grid_u8[0].len();
-    let start = (0, 0);
-    let end = (rows - 1, cols - 1);
+    let grid = match generated {
+        Some(grid) => grid,
+        None => decode_map(&map_data, map_format, args.map.parse_options(args.strict))?,
+    };
+
+    let rows = grid.rows();
+    let cols = grid.cols();
+    let (start, end) = args.map.endpoints(rows, cols)?;
+    for &(r, c) in &args.via {
+        if r >= rows || c >= cols {
+            return Err(format!("Coordinate ({},{}) is outside the {}x{} grid", r, c, rows, cols).into());
+        }
+    }
+    if args.optimize_order && args.via.len() > MAX_OPTIMIZED_WAYPOINTS {
+        return Err(format!("--optimize-order supports at most {} waypoints", MAX_OPTIMIZED_WAYPOINTS).into());
+    }
     
-    println!("Grid size: {}x{}", rows, cols);
-    println!("Start: ({},0) = 0x{:02X}", start.0, grid_u8[start.0][start.1]);
-    println!("End: ({},{}) = 0x{:02X}", end.0, end.1, grid_u8[end.0][end.1]);
+    if text {
+        println!("Grid size: {}x{}", rows, cols);
+        println!("Start: ({},{}) = {}", start.0, start.1, cell_label(&grid, start));
+        println!("End: ({},{}) = {}", end.0, end.1, cell_label(&grid, end));
+        for &(r, c) in &args.via {
+            println!("Via: ({},{}) = {}", r, c, cell_label(&grid, (r, c)));
+        }
+    }
     
-    if args.generate.is_some() {
+    if text && args.generate.is_some() {
         println!("\nGenerated Map:");
-        for row in grid_str_vec.as_ref().unwrap() {
-            println!("{}", row.join(" "));
-        }
+        println!("{}", grid);
     }
     
     if let Some(ref filename) = args.output {
-        if args.generate.is_some() && grid_str_vec.is_some() {
-            let output_content = grid_str_vec.as_ref().unwrap().iter()
-                .map(|r| r.join(" "))
-                .collect::<Vec<String>>()
-                .join("\n");
-            fs::write(filename, output_content)?;
+        let format = MapFormat::from_extension(Path::new(filename)).unwrap_or(MapFormat::Text);
+        fs::write(filename, encode_map(&grid, format, args.map.wall_value)?)?;
+        if text {
             println!("\nMap saved to: {}", filename);
         }
     }
     
-    let min_path_result = dijkstra(&grid_u8, start, end);
-    let max_path_result = max_path_dfs(&grid_u8, start, end);
+    let movement = args.map.movement.movement()?;
+    if let Some(filename) = &args.distance_field {
+        let source = if args.from_end { end } else { start };
+        return write_distance_field(&args, &grid, filename, source, movement);
+    }
+    if args.k == Some(0) {
+        return Err("--k must be at least 1".into());
+    }
+    if args.k.is_some() && grid.has_negative() {
+        return Err("--k needs non-negative costs".into());
+    }
+    if args.k.is_some() && !args.via.is_empty() {
+        return Err("--k cannot be combined with --via".into());
+    }
+    if args.max_steps.is_some() && grid.has_negative() {
+        return Err("--max-steps needs non-negative costs".into());
+    }
+    if args.max_steps.is_some() && (!args.via.is_empty() || args.k.is_some()) {
+        return Err("--max-steps cannot be combined with --via or --k".into());
+    }
+    if let (Some(min_cost), Some(budget)) = (args.min_cost, args.budget) {
+        if min_cost > budget {
+            return Err("--min-cost must not exceed --budget".into());
+        }
+    }
+    // --budget est une contrainte sur le chemin maximal, qu'il suffit de demander
+    let both = args.both || args.budget.is_some();
+    let render_format = match &args.render {
+        Some(filename) => Some(ImageFormat::from_extension(Path::new(filename))
+            .ok_or("--render expects a .svg or .png file")?),
+        None => None,
+    };
+
+    let mut animator = (args.animate && text).then(|| {
+        Animator::new(&grid, movement.topology, Duration::from_millis(args.frame_delay))
+    });
+    // L'animation a besoin de la recherche elle-même, le cache est alors ignoré ; une carte générée
+    // au hasard ne sera sans doute jamais redemandée, elle n'y prendrait qu'une place inutile
+    let cache = if args.no_cache || animator.is_some() || args.generate.is_some() { None } else { Cache::default_dir().map(Cache::new) };
+    if animator.is_some() {
+        println!("\nSEARCH ANIMATION (frontier in YELLOW, visited in BLUE):");
+    }
+    let observer: &mut dyn SearchObserver = match animator.as_mut() {
+        Some(animator) => animator,
+        None => &mut (),
+    };
+    let min_solver: Box<dyn Solver> = match args.max_steps {
+        Some(max_steps) => Box::new(StepLimited { movement, max_steps }),
+        None => args.algorithm.for_grid(&grid, movement, args.heuristic)?,
+    };
+    let blocked = [start, end].iter().chain(&args.via).any(|&pos| grid.is_wall(pos));
+    let min_started = Instant::now();
+    let mut min_params = format!("{} {:?} {:?} {:?} {:?} {:?} {}", min_solver.name(), args.heuristic,
+                                 movement, start, end, args.via, args.optimize_order);
+    if let Some(max_steps) = args.max_steps {
+        min_params.push_str(&format!(" {}", max_steps));
+    }
+    let min_key = cache_key("min", &grid, &min_params);
+    let cached_route: Option<Option<Route>> = cache.as_ref().and_then(|cache| cache.load(&min_key));
+    let min_cached = cached_route.is_some();
+    let route = match cached_route {
+        _ if blocked => None,
+        Some(route) => route,
+        None => {
+            let route = plan_route(min_solver.as_ref(), &grid, start, end, &args.via, args.optimize_order, observer)
+                .map_err(|cycle| format!("No minimum cost path: {}", cycle))?;
+            if let Some(cache) = &cache {
+                let _ = cache.store(&min_key, &route);
+            }
+            route
+        }
+    };
+    let min_elapsed = min_started.elapsed();
+    if let Some(animator) = animator.as_mut() {
+        animator.finish(route.as_ref().map(|r| r.path.as_slice()));
+    }
+    let min_path_result = route.as_ref().map(|r| (r.cost, r.path.clone()));
+
+    let mut k_stats = SearchStats::default();
+    let k_started = Instant::now();
+    let alternatives = match args.k {
+        Some(k) if !blocked => k_shortest_paths(&grid, start, end, movement, k, &mut k_stats),
+        _ => Vec::new(),
+    };
+    let k_elapsed = k_started.elapsed();
+
+    // La recherche exacte est coûteuse, seulement si le chemin maximal est demandé
+    let max_started = Instant::now();
+    let mut max_cached = false;
+    let max_path_result = if both && !blocked {
+        let budget = SearchBudget {
+            max_nodes: args.max_nodes,
+            time_limit: args.time_limit.map(Duration::from_millis),
+        };
+        let mut max_params = format!("{:?} {:?} {:?} {:?}", movement, start, end, budget);
+        if args.budget.is_some() {
+            max_params.push_str(&format!(" {:?} {:?}", args.budget, args.min_cost));
+        }
+        let max_key = cache_key("max", &grid, &max_params);
+        let cached: Option<Option<PathResult>> = cache.as_ref().and_then(|cache| cache.load(&max_key));
+        max_cached = cached.is_some();
+        cached.unwrap_or_else(|| {
+            let result = capped_max_path(&grid, start, end, movement, budget, args.budget, args.min_cost);
+            if let Some(cache) = &cache {
+                let _ = cache.store(&max_key, &result);
+            }
+            result
+        })
+    } else {
+        None
+    };
+
+    let max_elapsed = max_started.elapsed();
+    // Sans chemin maximal sous budget, le minimum tranche : s'il dépasse le budget, aucun chemin ne tient dedans
+    let over_budget = args.max_steps.is_none() && args.budget.is_some_and(|budget| {
+        min_path_result.as_ref().is_none_or(|&(cost, _)| cost > budget)
+    });
+    // Un chemin de coût compris entre --min-cost et --budget existe si le maximum sous budget atteint --min-cost
+    let in_range = args.min_cost.map(|min_cost| match &max_path_result {
+        Some(result) if result.cost >= min_cost => Some(true),
+        Some(result) if result.optimal => Some(false),
+        None if over_budget => Some(false),
+        _ => None,
+    });
+
+    if let (Some(filename), Some(format)) = (&args.render, render_format) {
+        let mut overlays = Vec::new();
+        if let Some(result) = &max_path_result {
+            overlays.push(Overlay { path: &result.path, color: MAX_PATH_RGB });
+        }
+        if alternatives.is_empty() {
+            if let Some(route) = &route {
+                overlays.push(Overlay { path: &route.path, color: MIN_PATH_RGB });
+            }
+        }
+        // Le meilleur chemin est dessiné en dernier, par-dessus les autres
+        for (rank, (_, path)) in alternatives.iter().enumerate().rev() {
+            overlays.push(Overlay { path, color: ALTERNATIVE_RGB[rank % ALTERNATIVE_RGB.len()] });
+        }
+        let options = ImageOptions { topology: movement.topology, cell_size: args.cell_size, labels: args.labels };
+        let image = match format {
+            ImageFormat::Svg => render_svg(&grid, &overlays, start, end, &options).into_bytes(),
+            ImageFormat::Png => render_png(&grid, &overlays, start, end, &options)?,
+        };
+        fs::write(filename, image)?;
+        if text {
+            println!("\nImage saved to: {}", filename);
+        }
+    }
+
+    if !text {
+        let report = Report {
+            rows,
+            cols,
+            start,
+            end,
+            via: args.via.clone(),
+            topology: movement.topology,
+            seed: generated_seed,
+            max_steps: args.max_steps,
+            budget: args.budget,
+            range: args.min_cost.zip(args.budget).map(|(min, max)| CostRange { min, max, exists: in_range.flatten() }),
+            min: route.map(|route| PathReport {
+                cost: route.cost,
+                length: route.path.len(),
+                optimal: true,
+                step_costs: step_costs(&route.path, &grid, movement),
+                legs: if args.via.is_empty() {
+                    Vec::new()
+                } else {
+                    route.legs.iter().map(|leg| LegReport { from: leg.from, to: leg.to, cost: leg.cost }).collect()
+                },
+                path: route.path,
+                solver: SolverInfo {
+                    algorithm: min_solver.name(),
+                    nodes_expanded: route.nodes_expanded,
+                    elapsed_ms: min_elapsed.as_secs_f64() * 1000.0,
+                    cached: min_cached,
+                },
+            }),
+            alternatives: alternatives.iter().enumerate().map(|(rank, (cost, path))| AlternativeReport {
+                rank: rank + 1,
+                overlap: path_overlap(path, &alternatives[0].1),
+                path: PathReport {
+                    cost: *cost,
+                    length: path.len(),
+                    optimal: true,
+                    step_costs: step_costs(path, &grid, movement),
+                    legs: Vec::new(),
+                    path: path.clone(),
+                    solver: SolverInfo {
+                        algorithm: "yen",
+                        nodes_expanded: k_stats.nodes_expanded,
+                        elapsed_ms: k_elapsed.as_secs_f64() * 1000.0,
+                        cached: false,
+                    },
+                },
+            }).collect(),
+            max: max_path_result.map(|result| PathReport {
+                cost: result.cost,
+                length: result.path.len(),
+                optimal: result.optimal,
+                step_costs: step_costs(&result.path, &grid, movement),
+                legs: Vec::new(),
+                path: result.path,
+                solver: SolverInfo {
+                    algorithm: "branch-and-bound",
+                    nodes_expanded: result.nodes_expanded,
+                    elapsed_ms: max_elapsed.as_secs_f64() * 1000.0,
+                    cached: max_cached,
+                },
+            }),
+        };
+        match args.format {
+            OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+            _ => write_csv_report(&report),
+        }
+        return Ok(());
+    }
+
+    if min_cached || max_cached {
+        println!("\n(results read from the cache, pass --no-cache to recompute them)");
+    }
+
+    if min_path_result.is_none() {
+        match ([start, end].iter().chain(&args.via).find(|&&pos| grid.is_wall(pos)), args.max_steps) {
+            (Some(&(r, c)), _) => println!("\nNo path: ({},{}) is a wall", r, c),
+            (None, Some(max_steps)) => println!("\nNo path from ({},{}) to ({},{}) within {} steps", start.0, start.1, end.0, end.1, max_steps),
+            (None, None) => println!("\nNo path from ({},{}) to ({},{})", start.0, start.1, end.0, end.1),
+        }
+    }
 
     // Si pas de flags, afficher par défaut les résultats
-    let should_visualize = args.visualize || args.both || args.animate || 
-                          (args.map_file.is_some() && !args.generate.is_some());
+    let should_visualize = args.visualize || both || args.animate || 
+                          (args.map_file.is_some() && args.generate.is_none());
 
     if should_visualize {
         println!("\nHEXADECIMAL GRID (rainbow gradient):");
         println!("==================================================");
-        visualize_map(grid_str_vec.as_ref().unwrap(), None, "");
+        visualize_map(&grid, None, "", movement.topology);
 
         if let Some((cost, path)) = &min_path_result {
             println!("\nMINIMUM COST PATH (shown in WHITE):");
-            visualize_map(grid_str_vec.as_ref().unwrap(), Some(path), "\x1b[37m");
-            print_path_details("MINIMUM", *cost, path, &grid_u8);
+            visualize_map(&grid, Some(path), "\x1b[37m", movement.topology);
+            print_path_details("MINIMUM", *cost, path, &grid, movement);
         }
 
-        if args.both {
-            if let Some((cost, path)) = &max_path_result {
-                println!("\nMAXIMUM COST PATH (shown in RED):");
-                visualize_map(grid_str_vec.as_ref().unwrap(), Some(path), "\x1b[31m");
-                print_path_details("MAXIMUM", *cost, path, &grid_u8);
+        if let Some(route) = route.as_ref().filter(|_| !args.via.is_empty()) {
+            print_route_legs(route);
+        }
+
+        if !alternatives.is_empty() {
+            print_alternatives(&alternatives);
+            println!("\n(ranked paths shown in WHITE, YELLOW, CYAN, MAGENTA, GREEN, BLUE)");
+            let paths: Vec<(&[Coord], &str)> = alternatives.iter().enumerate()
+                .map(|(rank, (_, path))| (path.as_slice(), ALTERNATIVE_ANSI[rank % ALTERNATIVE_ANSI.len()]))
+                .collect();
+            visualize_paths(&grid, &paths, movement.topology);
+        }
+
+        if both {
+            match &max_path_result {
+                Some(result) => {
+                    match args.budget {
+                        Some(budget) => println!("\nMAXIMUM COST PATH UNDER BUDGET {} ({}) (shown in RED):", hex_cost(budget), budget),
+                        None => println!("\nMAXIMUM COST PATH (shown in RED):"),
+                    }
+                    if !args.via.is_empty() {
+                        println!("(waypoints are not used by the maximum path search)");
+                    }
+                    visualize_map(&grid, Some(&result.path), "\x1b[31m", movement.topology);
+                    print_path_details("MAXIMUM", result.cost, &result.path, &grid, movement);
+                    if result.optimal {
+                        println!("Search: proven optimal ({} nodes explored)", result.nodes_expanded);
+                    } else if args.min_cost.is_some_and(|min_cost| result.cost >= min_cost) {
+                        println!("Search: stopped at the first path reaching --min-cost ({} nodes explored)", result.nodes_expanded);
+                    } else {
+                        println!("Search: best found, budget exhausted after {} nodes", result.nodes_expanded);
+                    }
+                }
+                None if min_path_result.is_none() => println!("\nMAXIMUM COST PATH: no path"),
+                None if over_budget => println!("\nMAXIMUM COST PATH: every path costs more than the budget"),
+                None => println!("\nMAXIMUM COST PATH: none found within budget"),
             }
         }
     } else if let Some((cost, _)) = &min_path_result {
-        println!("\nMinimum cost path found: 0x{:X} ({})", cost, cost);
+        println!("\nMinimum cost path found: {} ({})", hex_cost(*cost), cost);
+        if let Some(route) = route.as_ref().filter(|_| !args.via.is_empty()) {
+            print_route_legs(route);
+        }
+        if !alternatives.is_empty() {
+            print_alternatives(&alternatives);
+        }
+    }
+
+    if let (Some(min_cost), Some(budget)) = (args.min_cost, args.budget) {
+        let answer = match in_range.flatten() {
+            Some(true) => "yes",
+            Some(false) => "no",
+            None => "unknown, the search budget ran out",
+        };
+        println!("\nPath with cost between {} and {}: {}", min_cost, budget, answer);
+    }
+
+    Ok(())
+}
+
+fn write_distance_field(args: &Cli, grid: &Grid, filename: &str, source: Coord, movement: Movement) -> Result<(), Box<dyn std::error::Error>> {
+    if grid.has_negative() {
+        return Err("--distance-field needs non-negative costs".into());
+    }
+    if grid.is_wall(source) {
+        return Err(format!("The field source ({},{}) is a wall", source.0, source.1).into());
+    }
+    let text = args.format == OutputFormat::Text;
+    let field = distance_field(grid, source, movement, args.from_end);
+    fs::write(filename, field.distance_grid()?.to_string())?;
+    if let Some(directions) = &args.directions {
+        fs::write(directions, field.direction_grid(movement.topology).to_string())?;
+    }
+    if !text {
+        return Ok(());
     }
 
+    let max = field.max_distance().unwrap_or(0);
+    println!("\nDistance field {} ({},{}): {} of {} cells reached, farthest at {} ({})",
+             if args.from_end { "to" } else { "from" }, source.0, source.1, field.reached(), grid.len(), hex_cost(max), max);
+    println!("Distances saved to: {}", filename);
+    if let Some(directions) = &args.directions {
+        println!("Directions saved to: {}", directions);
+    }
+    if args.heatmap {
+        println!("\nDISTANCE HEATMAP (00 at the source, FF at the farthest cell):");
+        visualize_map(&field.heatmap(), None, "", movement.topology);
+    }
     Ok(())
-}
\ No newline at end of file
+}
+
+fn run_layered(args: &Cli, map_text: &str) -> Result<(), Box<dyn std::error::Error>> {
+    if args.both || args.k.is_some() || !args.via.is_empty() || args.animate || args.render.is_some() || args.distance_field.is_some() || args.output.is_some() {
+        return Err("layered maps only support the minimum path, without --both, --k, --via, --animate, --render, --distance-field or --output".into());
+    }
+    if args.format == OutputFormat::Csv {
+        return Err("layered maps support --format text or json".into());
+    }
+    let grid = parse_layers(map_text, args.map.parse_options(args.strict))?;
+    if grid.has_negative() {
+        return Err("layered maps need non-negative costs".into());
+    }
+    let movement = args.map.movement.movement()?;
+
+    let (rows, cols, layers) = (grid.rows(), grid.cols(), grid.layers.len());
+    let mode = args.layer_mode;
+    if mode == LayerMode::Time && args.end_layer.is_some() {
+        return Err("--end-layer cannot be used with --layer-mode time, the end can be reached at any step".into());
+    }
+    if mode == LayerMode::Time && !grid.connectors.is_empty() {
+        return Err("connectors (`@` lines) only apply to --layer-mode floors".into());
+    }
+    let (start, end) = args.map.endpoints(rows, cols)?;
+    let (start, end) = ((args.start_layer, start), (args.end_layer.unwrap_or(layers - 1), end));
+    for layer in [start.0, end.0] {
+        if layer >= layers {
+            return Err(format!("Layer {} does not exist, the map has {} layers", layer, layers).into());
+        }
+    }
+
+    let text = args.format == OutputFormat::Text;
+    if text {
+        println!("Grid size: {}x{}, {} layers ({})", rows, cols, layers, if mode == LayerMode::Time { "time slices" } else { "floors" });
+        println!("Start: {}:{},{} = {}", start.0, start.1 .0, start.1 .1, cell_label(&grid.layers[start.0], start.1));
+        match mode {
+            LayerMode::Floors => println!("End: {}:{},{} = {}", end.0, end.1 .0, end.1 .1, cell_label(&grid.layers[end.0], end.1)),
+            LayerMode::Time => println!("End: ({},{}) at any step", end.1 .0, end.1 .1),
+        }
+        if mode == LayerMode::Floors {
+            println!("Connectors: {}", grid.connectors.len());
+        }
+    }
+
+    let started = Instant::now();
+    let result = layered_dijkstra(&grid, start, end, movement, mode);
+    let elapsed = started.elapsed();
+    // En mode temps, la couche d'arrivée est la tranche où le chemin atteint la fin
+    let end = match (mode, &result) {
+        (LayerMode::Time, Some(result)) => result.path[result.path.len() - 1],
+        _ => end,
+    };
+
+    if !text {
+        let report = LayeredReport {
+            rows,
+            cols,
+            layers,
+            mode,
+            start,
+            end,
+            topology: movement.topology,
+            min: result.map(|result| LayeredPathReport {
+                cost: result.cost,
+                length: result.path.len(),
+                step_costs: grid.step_costs(&result.path, movement, mode),
+                path: result.path,
+                solver: SolverInfo {
+                    algorithm: "dijkstra",
+                    nodes_expanded: result.nodes_expanded,
+                    elapsed_ms: elapsed.as_secs_f64() * 1000.0,
+                    cached: false,
+                },
+            }),
+        };
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+
+    for (layer, layer_grid) in grid.layers.iter().enumerate() {
+        let on_layer: Vec<Coord> = result.iter().flat_map(|result| &result.path).filter(|&&(l, _)| l == layer).map(|&(_, pos)| pos).collect();
+        println!("\n{} {} (path shown in WHITE):", if mode == LayerMode::Time { "TIME SLICE" } else { "LAYER" }, layer);
+        visualize_map(layer_grid, Some(&on_layer), "\x1b[37m", movement.topology);
+    }
+    match &result {
+        Some(result) => print_layered_path(result.cost, &result.path, &grid.step_costs(&result.path, movement, mode), &grid, mode),
+        None => println!("\nNo path from {}:{},{} to {}:{},{}", start.0, start.1 .0, start.1 .1, end.0, end.1 .0, end.1 .1),
+    }
+    Ok(())
+}
+
+// Lit et décode une carte, avec le format détecté d'après l'extension et le contenu
+fn load_grid(path: &str, options: &ParseOptions) -> Result<(Grid, MapFormat), Box<dyn std::error::Error>> {
+    let map_data = fs::read(path)?;
+    let map_format = MapFormat::detect(Path::new(path), &map_data);
+    let grid = decode_map(&map_data, map_format, *options).map_err(|e| format!("{}: {}", path, e))?;
+    Ok((grid, map_format))
+}
+
+fn run_edit(args: &EditArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let (grid, map_format) = load_grid(&args.map_file, &args.map.parse_options(false))?;
+    let (start, end) = args.map.endpoints(grid.rows(), grid.cols())?;
+
+    // L'éditeur n'écrit que le format texte, sans écraser un fichier binaire
+    let path = Path::new(&args.map_file);
+    let save_path = match map_format {
+        MapFormat::Text => path.to_path_buf(),
+        _ => path.with_extension("txt"),
+    };
+    let options = EditorOptions {
+        movement: args.map.movement.movement()?,
+        budget: SearchBudget { max_nodes: args.max_nodes, time_limit: Some(Duration::from_millis(args.time_limit)) },
+        start,
+        end,
+    };
+    run_editor(grid, save_path, options)?;
+    Ok(())
+}
+
+fn run_bench(args: &BenchArgs) -> Result<(), Box<dyn std::error::Error>> {
+    if args.sizes.is_empty() || args.sizes.contains(&0) {
+        return Err("--sizes must list sizes of at least 1".into());
+    }
+    if args.runs == 0 {
+        return Err("--runs must be at least 1".into());
+    }
+    if !(0.0..=1.0).contains(&args.wall_density) {
+        return Err("--wall-density must be between 0 and 1".into());
+    }
+    let movement = args.movement.movement()?;
+    let seed = args.seed.unwrap_or_else(|| rand::thread_rng().gen());
+    let options = BenchOptions {
+        runs: args.runs,
+        seed,
+        movement,
+        budget: SearchBudget { max_nodes: args.max_nodes, time_limit: Some(Duration::from_millis(args.time_limit)) },
+        wall_density: args.wall_density,
+        terrain: Terrain { distribution: args.distribution, ..Terrain::default() },
+    };
+
+    let text = args.format == OutputFormat::Text;
+    if text {
+        println!("Benchmarking {} runs per size (seed {}, max path budget {} nodes or {} ms)\n",
+                 args.runs, seed, args.max_nodes, args.time_limit);
+        println!("{}", header_line());
+    }
+    let mut sizes = Vec::new();
+    for &size in &args.sizes {
+        let report = bench_size(size, &options);
+        if text {
+            for line in size_lines(&report) {
+                println!("{}", line);
+            }
+        }
+        sizes.push(report);
+    }
+
+    let report = BenchReport { seed, runs: args.runs, topology: movement.topology, max_nodes: args.max_nodes, time_limit_ms: args.time_limit, sizes };
+    match args.format {
+        OutputFormat::Text => {}
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+        OutputFormat::Csv => write_bench_csv(&report),
+    }
+    Ok(())
+}
+
+fn run_batch_command(args: &BatchArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let movement = args.map.movement.movement()?;
+    if args.jobs == Some(0) {
+        return Err("--jobs must be at least 1".into());
+    }
+    let files = collect_inputs(&args.inputs)?;
+    let options = BatchOptions {
+        parse: args.map.parse_options(args.strict),
+        movement,
+        algorithm: args.algorithm,
+        heuristic: args.heuristic,
+        max_path: args.both.then(|| SearchBudget {
+            max_nodes: args.max_nodes,
+            time_limit: args.time_limit.map(Duration::from_millis),
+        }),
+        start: args.map.start,
+        end: args.map.end,
+    };
+    // 0 laisse rayon prendre un thread par cœur
+    let report = run_batch(&files, args.jobs.unwrap_or(0), &options)?;
+
+    match args.format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+        OutputFormat::Csv => write_batch_csv(&report),
+        OutputFormat::Text => {
+            let describe = |name: &str, path: &Option<PathSummary>| match path {
+                Some(path) => format!("  {} {} ({}, {} cells{})", name, hex_cost(path.cost), path.cost, path.length,
+                                      if path.optimal { "" } else { ", best found" }),
+                None => format!("  {} none", name),
+            };
+            for result in &report.results {
+                match &result.error {
+                    Some(error) => println!("{}: error: {}", result.file, error),
+                    None => println!("{}: {}x{}{}{}", result.file, result.rows, result.cols, describe("min", &result.min),
+                                     if args.both { describe("max", &result.max) } else { String::new() }),
+                }
+            }
+            println!("\n{} maps, {} solved, {} failed in {:.1} ms on {} threads",
+                     report.files, report.solved, report.failed, report.elapsed_ms, report.jobs);
+        }
+    }
+    // Les erreurs de lecture restent visibles quand stdout est redirigé vers le résumé
+    if args.format != OutputFormat::Text {
+        for result in &report.results {
+            if let Some(error) = &result.error {
+                eprintln!("{}: {}", result.file, error);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn run_verify(args: &VerifyArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let (grid, _) = load_grid(&args.map_file, &args.map.parse_options(false))?;
+    let movement = args.map.movement.movement()?;
+    let route = match (&args.path, &args.path_file) {
+        (Some(route), _) => route.clone(),
+        (None, Some(filename)) => fs::read_to_string(filename)?,
+        (None, None) => unreachable!(),
+    };
+    let path = parse_path(&route).map_err(|e| format!("Invalid route: {}", e))?;
+
+    let (start, end) = args.map.endpoints(grid.rows(), grid.cols())?;
+    let verification = verify_path(&grid, &path, start, end, args.algorithm, movement, args.heuristic)?;
+
+    match args.format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&verification)?),
+        OutputFormat::Csv => write_verify_csv(&verification),
+        OutputFormat::Text => {
+            println!("Verifying a route of {} cells on the {}x{} grid...", path.len(), grid.rows(), grid.cols());
+            if verification.valid {
+                println!("\nSUPPLIED ROUTE (shown in RED):");
+                visualize_map(&grid, Some(&path), "\x1b[31m", movement.topology);
+                print_path_details("SUPPLIED", verification.cost.unwrap_or(0), &path, &grid, movement);
+            } else {
+                println!("\nINVALID ROUTE ({} issue{}):", verification.issues.len(), if verification.issues.len() > 1 { "s" } else { "" });
+                for issue in &verification.issues {
+                    println!("  step {}: {}", issue.step, issue.message);
+                }
+                if let Some(cost) = verification.cost {
+                    println!("Cost as drawn: {} ({})", hex_cost(cost), cost);
+                }
+            }
+            match (verification.optimum, verification.gap) {
+                (Some(optimum), Some(0)) => println!("\nOptimum: {} ({}), the route is optimal", hex_cost(optimum), optimum),
+                (Some(optimum), Some(gap)) => println!("\nOptimum: {} ({}), the route costs {:+} more ({:+.2}%)",
+                                                       hex_cost(optimum), optimum, gap, verification.gap_percent.unwrap_or(0.0)),
+                (Some(optimum), None) => println!("\nOptimum: {} ({})", hex_cost(optimum), optimum),
+                (None, _) => println!("\nOptimum: no path from ({},{}) to ({},{})", start.0, start.1, end.0, end.1),
+            }
+        }
+    }
+    if !verification.valid {
+        return Err(format!("the route is invalid ({} issue{})", verification.issues.len(), if verification.issues.len() > 1 { "s" } else { "" }).into());
+    }
+    Ok(())
+}
+
+fn run_diff(args: &DiffArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let options = args.map.parse_options(false);
+    let ((old, _), (new, _)) = (load_grid(&args.old_file, &options)?, load_grid(&args.new_file, &options)?);
+    let (old, new) = (&old, &new);
+    let movement = args.map.movement.movement()?;
+    let (start, end) = args.map.endpoints(old.rows(), old.cols())?;
+    let diff_options = DiffOptions {
+        movement,
+        algorithm: args.algorithm,
+        heuristic: args.heuristic,
+        max_path: args.both.then(|| SearchBudget { max_nodes: args.max_nodes, time_limit: args.time_limit.map(Duration::from_millis) }),
+    };
+    let diff = diff_maps(old, new, start, end, &diff_options)?;
+
+    match args.format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&diff)?),
+        OutputFormat::Csv => write_diff_csv(&diff),
+        OutputFormat::Text => {
+            println!("Comparing {} and {} ({}x{})...", args.old_file, args.new_file, diff.rows, diff.cols);
+            println!("\nCHANGED CELLS: {} of {}", diff.changed.len(), old.len());
+            for change in diff.changed.iter().take(MAX_LISTED_CHANGES) {
+                let (r, c) = change.pos;
+                println!("  ({},{}) {} -> {}", r, c, cell_label(old, change.pos), cell_label(new, change.pos));
+            }
+            if diff.changed.len() > MAX_LISTED_CHANGES {
+                println!("  ... and {} more (--format json or csv lists them all)", diff.changed.len() - MAX_LISTED_CHANGES);
+            }
+            print_path_comparison("MINIMUM", &diff.min, old, new, &diff.changed, "\x1b[37m", movement.topology);
+            if let Some(max) = &diff.max {
+                print_path_comparison("MAXIMUM", max, old, new, &diff.changed, "\x1b[31m", movement.topology);
+            }
+        }
+    }
+    Ok(())
+}
+
+// Au-delà, la liste des cellules modifiées n'est plus lisible dans le terminal
+const MAX_LISTED_CHANGES: usize = 20;
+
+fn print_path_comparison(name: &str, comparison: &PathComparison, old: &Grid, new: &Grid, changed: &[CellChange], path_color: &str, topology: Topology) {
+    println!("\n{} COST PATH:", name);
+    println!("==========================");
+    let describe = |result: &Option<PathResult>| match result {
+        Some(result) => format!("{} ({}) in {} steps{}", hex_cost(result.cost), result.cost, result.path.len(),
+                                if result.optimal { "" } else { ", best found within budget" }),
+        None => "no path".to_string(),
+    };
+    println!("Old: {}", describe(&comparison.old));
+    println!("New: {}", describe(&comparison.new));
+    let (Some(old_result), Some(new_result)) = (&comparison.old, &comparison.new) else { return };
+    let delta = comparison.delta.unwrap_or(0);
+    println!("Delta: {}{} ({:+}), {:.1}% of the new path was already used",
+             if delta < 0 { "-" } else { "+" }, hex_cost(delta.abs()), delta, comparison.shared.unwrap_or(0.0));
+
+    if comparison.segments.is_empty() {
+        println!("The path did not move");
+    } else {
+        println!("\nDiverging segments ({}):", comparison.segments.len());
+        let cells = |cells: &[Coord]| cells.iter().map(|&(r, c)| format!("({},{})", r, c)).collect::<Vec<_>>().join("->");
+        let end = |pos: Option<Coord>, fallback: &str| pos.map_or(fallback.to_string(), |(r, c)| format!("({},{})", r, c));
+        for (i, segment) in comparison.segments.iter().enumerate() {
+            println!("#{} from {} to {}: cost {} -> {} ({:+})", i + 1, end(segment.from, "the start"), end(segment.to, "the end"),
+                     segment.old_cost, segment.new_cost, segment.new_cost - segment.old_cost);
+            println!("   old: {}", if segment.old.is_empty() { "direct".to_string() } else { cells(&segment.old) });
+            println!("   new: {}", if segment.new.is_empty() { "direct".to_string() } else { cells(&segment.new) });
+        }
+    }
+
+    // Tronçons propres à chaque chemin en JAUNE, par-dessus le chemin ; cellules modifiées en MAGENTA
+    let changed: Vec<Coord> = changed.iter().map(|change| change.pos).collect();
+    let old_only: Vec<Coord> = comparison.segments.iter().flat_map(|segment| segment.old.iter().copied()).collect();
+    let new_only: Vec<Coord> = comparison.segments.iter().flat_map(|segment| segment.new.iter().copied()).collect();
+    println!("\n{} PATHS (path in {}, diverging cells in YELLOW, changed cells in MAGENTA):",
+             name, if path_color == "\x1b[31m" { "RED" } else { "WHITE" });
+    visualize_side_by_side(
+        (old, &[(&old_only, "\x1b[33m"), (&old_result.path, path_color), (&changed, "\x1b[35m")]),
+        (new, &[(&new_only, "\x1b[33m"), (&new_result.path, path_color), (&changed, "\x1b[35m")]),
+        ("OLD", "NEW"),
+        topology,
+    );
+}
+
+fn run_cache(args: &CacheArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let dir = Cache::default_dir().ok_or("cannot locate the cache directory, set XDG_CACHE_HOME")?;
+    match args.action {
+        CacheAction::Clear => {
+            let removed = Cache::new(dir.clone()).clear()?;
+            println!("Removed {} cached result{} from {}", removed, if removed == 1 { "" } else { "s" }, dir.display());
+        }
+    }
+    Ok(())
+}
+
+fn parse_coord(s: &str) -> Result<Coord, String> {
+    let (r, c) = s.split_once(',').ok_or_else(|| format!("expected r,c but got '{}'", s))?;
+    let r = r.trim().parse::<usize>().map_err(|e| format!("invalid row '{}': {}", r, e))?;
+    let c = c.trim().parse::<usize>().map_err(|e| format!("invalid column '{}': {}", c, e))?;
+    Ok((r, c))
+}
+
+// Coût en décimal, ou en hexadécimal avec le préfixe 0x comme ceux qui sont affichés
+fn parse_cost(s: &str) -> Result<i64, String> {
+    let (negative, digits) = match s.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, s),
+    };
+    let value = match digits.strip_prefix("0x").or_else(|| digits.strip_prefix("0X")) {
+        Some(hex) => i64::from_str_radix(hex, 16),
+        None => digits.parse::<i64>(),
+    };
+    let value = value.map_err(|e| format!("invalid cost '{}': {}", s, e))?;
+    Ok(if negative { -value } else { value })
+}
+
+fn parse_hex_value(s: &str) -> Result<u32, String> {
+    u32::from_str_radix(s.trim_start_matches("0x"), 16).map_err(|e| format!("invalid hex value '{}': {}", s, e))
+}
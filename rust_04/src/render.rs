@@ -0,0 +1,328 @@
+use crate::grid::Grid;
+use crate::layers::{LayerCoord, LayerMode, LayeredGrid};
+use crate::route::Route;
+use crate::solver::{path_overlap, SearchObserver};
+use crate::topology::{Movement, Topology};
+use crate::Coord;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::io::{self, IsTerminal, Write};
+use std::thread;
+use std::time::Duration;
+
+pub const WALL_ANSI: &str = "\x1b[38;5;240m";
+
+// Index dans le cube 6x6x6 de la palette xterm 256 couleurs
+fn rainbow_index(value: u8) -> u8 {
+    16 + (value as f32 / 255.0 * 215.0).round() as u8
+}
+
+pub fn hex_to_rainbow_ansi(value: u8) -> String {
+    format!("\x1b[38;5;{}m", rainbow_index(value))
+}
+
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+const WALL_RGB: (u8, u8, u8) = (88, 88, 88);
+
+/// Couleur réelle correspondant à `hex_to_rainbow_ansi`
+pub fn rainbow_rgb(value: u8) -> (u8, u8, u8) {
+    let i = (rainbow_index(value) - 16) as usize;
+    (CUBE_LEVELS[i / 36], CUBE_LEVELS[i / 6 % 6], CUBE_LEVELS[i % 6])
+}
+
+// `intensity` vient de `Grid::intensity`, None pour un mur
+pub fn cell_rgb(intensity: Option<u8>) -> (u8, u8, u8) {
+    intensity.map_or(WALL_RGB, rainbow_rgb)
+}
+
+pub(crate) fn cell_ansi(intensity: Option<u8>) -> String {
+    match intensity {
+        Some(value) => hex_to_rainbow_ansi(value),
+        None => WALL_ANSI.to_string(),
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum CellState {
+    Untouched,
+    Frontier,
+    Visited,
+    Path,
+}
+
+const FRONTIER_ANSI: &str = "\x1b[30;43m";
+const VISITED_ANSI: &str = "\x1b[37;44m";
+const PATH_ANSI: &str = "\x1b[30;47m";
+
+// Redessine la grille sur place à chaque nœud développé
+pub struct Animator<'a> {
+    grid: &'a Grid,
+    topology: Topology,
+    cells: Vec<Vec<CellState>>,
+    delay: Duration,
+    tty: bool,
+    drawn: bool,
+    frames: u64,
+}
+
+impl<'a> Animator<'a> {
+    pub fn new(grid: &'a Grid, topology: Topology, delay: Duration) -> Self {
+        Animator {
+            grid,
+            topology,
+            cells: vec![vec![CellState::Untouched; grid.cols()]; grid.rows()],
+            delay,
+            tty: io::stdout().is_terminal(),
+            drawn: false,
+            frames: 0,
+        }
+    }
+
+    fn frame(&mut self) {
+        self.frames += 1;
+        if !self.tty {
+            return;
+        }
+
+        let mut out = io::stdout().lock();
+        if self.drawn {
+            // Remonter le curseur au début de la grille
+            let _ = write!(out, "\x1b[{}A", self.grid.rows());
+        }
+        for r in 0..self.grid.rows() {
+            let _ = write!(out, "\r\x1b[2K");
+            if self.topology == Topology::Hex && r % 2 == 1 {
+                let _ = write!(out, "  ");
+            }
+            for c in 0..self.grid.cols() {
+                let color = match self.cells[r][c] {
+                    CellState::Frontier => FRONTIER_ANSI.to_string(),
+                    CellState::Visited => VISITED_ANSI.to_string(),
+                    CellState::Path => PATH_ANSI.to_string(),
+                    CellState::Untouched => cell_ansi(self.grid.intensity((r, c))),
+                };
+                let _ = write!(out, "{}{}\x1b[0m ", color, self.grid.token((r, c)));
+            }
+            let _ = writeln!(out);
+        }
+        let _ = out.flush();
+        self.drawn = true;
+        thread::sleep(self.delay);
+    }
+
+    pub fn finish(&mut self, path: Option<&[Coord]>) {
+        for &(r, c) in path.unwrap_or_default() {
+            self.cells[r][c] = CellState::Path;
+        }
+        self.frame();
+        if !self.tty {
+            println!("Animation: {} frames (stdout is not a terminal)", self.frames);
+        }
+    }
+}
+
+impl SearchObserver for Animator<'_> {
+    fn started(&mut self, start: Coord) {
+        for row in self.cells.iter_mut() {
+            row.fill(CellState::Untouched);
+        }
+        self.cells[start.0][start.1] = CellState::Frontier;
+    }
+
+    fn discovered(&mut self, pos: Coord) {
+        if self.cells[pos.0][pos.1] == CellState::Untouched {
+            self.cells[pos.0][pos.1] = CellState::Frontier;
+        }
+    }
+
+    fn expanded(&mut self, pos: Coord) {
+        self.cells[pos.0][pos.1] = CellState::Visited;
+        self.frame();
+    }
+}
+
+pub fn visualize_map(grid: &Grid, path: Option<&[Coord]>, path_color: &str, topology: Topology) {
+    let paths: Vec<(&[Coord], &str)> = path.map(|p| (p, path_color)).into_iter().collect();
+    visualize_paths(grid, &paths, topology);
+}
+
+// Couleurs des chemins alternatifs, dans l'ordre de leur rang
+pub const ALTERNATIVE_ANSI: [&str; 6] = ["\x1b[37m", "\x1b[33m", "\x1b[36m", "\x1b[35m", "\x1b[32m", "\x1b[34m"];
+
+// Une cellule prend la couleur du premier chemin qui la traverse
+pub fn visualize_paths(grid: &Grid, paths: &[(&[Coord], &str)], topology: Topology) {
+    for line in map_lines(grid, paths, topology) {
+        println!("{}", line);
+    }
+}
+
+// Lignes colorées de la carte, sans retour à la ligne
+fn map_lines(grid: &Grid, paths: &[(&[Coord], &str)], topology: Topology) -> Vec<String> {
+    let mut cell_colors: HashMap<Coord, &str> = HashMap::new();
+    for &(path, color) in paths.iter().rev() {
+        for &pos in path {
+            cell_colors.insert(pos, color);
+        }
+    }
+
+    (0..grid.rows())
+        .map(|r| {
+            let mut line = String::new();
+            // Tuiles hexagonales : les lignes impaires sont décalées d'une demi-cellule
+            if topology == Topology::Hex && r % 2 == 1 {
+                line.push_str("  ");
+            }
+            for c in 0..grid.cols() {
+                let color = cell_ansi(grid.intensity((r, c)));
+                let text_color = cell_colors.get(&(r, c)).copied().unwrap_or("\x1b[0m");
+                line.push_str(&format!("{}{}{} ", color, text_color, grid.token((r, c))));
+            }
+            line.push_str("\x1b[0m");
+            line
+        })
+        .collect()
+}
+
+// Deux cartes de mêmes dimensions côte à côte, chacune avec ses chemins
+pub fn visualize_side_by_side(left: (&Grid, &[(&[Coord], &str)]), right: (&Grid, &[(&[Coord], &str)]), titles: (&str, &str), topology: Topology) {
+    // Largeur visible d'une carte, décalage hexagonal compris ; les codes ANSI n'en prennent pas
+    let width = left.0.cols() * (left.0.width().digits() + 1) + if topology == Topology::Hex { 2 } else { 0 };
+    println!("{:<width$}   {}", titles.0, titles.1);
+    let left_lines = map_lines(left.0, left.1, topology);
+    let right_lines = map_lines(right.0, right.1, topology);
+    for (r, (left_line, right_line)) in left_lines.iter().zip(&right_lines).enumerate() {
+        let shift = if topology == Topology::Hex && r % 2 == 1 { 2 } else { 0 };
+        let padding = width - left.0.cols() * (left.0.width().digits() + 1) - shift;
+        println!("{}{:padding$}   {}", left_line, "", right_line);
+    }
+}
+
+pub fn cell_label(grid: &Grid, pos: Coord) -> String {
+    let digits = grid.width().digits();
+    match grid.cell(pos) {
+        Some(value) if value < 0 => format!("-0x{:0digits$X}", value.unsigned_abs()),
+        Some(value) => format!("0x{:0digits$X}", value),
+        None => "wall".to_string(),
+    }
+}
+
+// Coût total en hexadécimal, signé en cas de récompenses
+pub fn hex_cost(cost: i64) -> String {
+    if cost < 0 {
+        format!("-0x{:X}", cost.unsigned_abs())
+    } else {
+        format!("0x{:X}", cost)
+    }
+}
+
+pub fn print_path_details(name: &str, cost: i64, path: &[Coord], grid: &Grid, movement: Movement) {
+    print!("{}", path_details(name, cost, path, grid, movement));
+}
+
+/// Texte affiché par `print_path_details`, relu tel quel par `verify::parse_path`
+pub fn path_details(name: &str, cost: i64, path: &[Coord], grid: &Grid, movement: Movement) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "\n{} COST PATH (shown in {}):", name, if name == "MINIMUM" { "white" } else { "red" });
+    let _ = writeln!(out, "==========================");
+    let _ = writeln!(out, "Total cost: {} ({} decimal)", hex_cost(cost), cost);
+    let _ = writeln!(out, "Path length: {} steps", path.len());
+
+    let _ = writeln!(out, "Path:");
+    for (i, &(r, c)) in path.iter().enumerate() {
+        let _ = write!(out, "({},{})", r, c);
+        if i < path.len() - 1 {
+            out.push_str("->");
+        }
+        if (i + 1) % 6 == 0 {
+            out.push('\n');
+        }
+    }
+    out.push('\n');
+
+    let _ = writeln!(out, "\nStep-by-step costs:");
+    let _ = writeln!(out, "Start {} ({},{})", cell_label(grid, path[0]), path[0].0, path[0].1);
+    for pair in path.windows(2) {
+        let curr = pair[1];
+        let step_cost = movement.step_cost(grid, pair[0], curr);
+        let _ = writeln!(out, "-> {} ({},{}) {:+}", cell_label(grid, curr), curr.0, curr.1, step_cost);
+    }
+    let _ = writeln!(out, "Total: {} ({})", hex_cost(cost), cost);
+    out
+}
+
+// Chemin à travers les couches, chaque pas noté `couche:ligne,colonne`
+pub fn print_layered_path(cost: i64, path: &[LayerCoord], step_costs: &[i64], grid: &LayeredGrid, mode: LayerMode) {
+    println!("\nMINIMUM COST PATH (shown in white):");
+    println!("==========================");
+    println!("Total cost: {} ({} decimal)", hex_cost(cost), cost);
+    println!("Path length: {} steps", path.len());
+
+    println!("Path:");
+    for (i, &(layer, (r, c))) in path.iter().enumerate() {
+        print!("{}:{},{}", layer, r, c);
+        if i < path.len() - 1 {
+            print!("->");
+        }
+        if (i + 1) % 6 == 0 {
+            println!();
+        }
+    }
+    println!();
+
+    println!("\nStep-by-step costs:");
+    let (layer, pos) = path[0];
+    println!("Start {} ({}:{},{})", cell_label(&grid.layers[layer], pos), layer, pos.0, pos.1);
+    for (pair, step_cost) in path.windows(2).zip(&step_costs[1..]) {
+        let (layer, pos) = pair[1];
+        let note = match mode {
+            LayerMode::Floors if pair[0].0 != layer => " connector",
+            LayerMode::Time if pair[0].1 == pos => " wait",
+            _ => "",
+        };
+        println!("-> {} ({}:{},{}) {:+}{}", cell_label(&grid.layers[layer], pos), layer, pos.0, pos.1, step_cost, note);
+    }
+    println!("Total: {} ({})", hex_cost(cost), cost);
+}
+
+pub fn print_route_legs(route: &Route) {
+    println!("\nROUTE LEGS:");
+    println!("==========================");
+    for (i, leg) in route.legs.iter().enumerate() {
+        println!("Leg {}: ({},{}) -> ({},{}) cost {} ({}) in {} steps",
+                 i + 1, leg.from.0, leg.from.1, leg.to.0, leg.to.1, hex_cost(leg.cost), leg.cost, leg.path.len());
+    }
+    println!("Route total: {} ({})", hex_cost(route.cost), route.cost);
+}
+
+// Chemins de Yen, comparés au premier (l'optimum)
+pub fn print_alternatives(alternatives: &[(i64, Vec<Coord>)]) {
+    println!("\nK SHORTEST PATHS:");
+    println!("==========================");
+    for (rank, (cost, path)) in alternatives.iter().enumerate() {
+        println!("#{} cost {} ({}) in {} steps, {:.1}% shared with the optimum",
+                 rank + 1, hex_cost(*cost), cost, path.len(), path_overlap(path, &alternatives[0].1));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::CellWidth;
+
+    #[test]
+    fn rainbow_spans_the_color_cube() {
+        assert_eq!(rainbow_rgb(0), (0, 0, 0));
+        assert_eq!(rainbow_rgb(255), (255, 255, 255));
+        assert_eq!(hex_to_rainbow_ansi(255), "\x1b[38;5;231m");
+        assert_eq!(cell_rgb(None), WALL_RGB);
+    }
+
+    #[test]
+    fn labels_keep_the_cell_width_and_sign() {
+        let grid = Grid::new(1, 3, vec![Some(0x2A), Some(-5), None]).with_cell_type(CellWidth::U16, true);
+        assert_eq!(cell_label(&grid, (0, 0)), "0x002A");
+        assert_eq!(cell_label(&grid, (0, 1)), "-0x0005");
+        assert_eq!(cell_label(&grid, (0, 2)), "wall");
+        assert_eq!((hex_cost(255), hex_cost(-255)), ("0xFF".to_string(), "-0xFF".to_string()));
+    }
+}
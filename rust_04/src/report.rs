@@ -0,0 +1,183 @@
+use crate::batch::BatchReport;
+use crate::bench::BenchReport;
+use crate::diff::MapDiff;
+use crate::grid::Grid;
+use crate::layers::{LayerCoord, LayerMode};
+use crate::topology::{Movement, Topology};
+use crate::verify::Verification;
+use crate::Coord;
+use clap::ValueEnum;
+use serde::Serialize;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    Text,
+    Json,
+    Csv,
+}
+
+#[derive(Serialize)]
+pub struct SolverInfo {
+    pub algorithm: &'static str,
+    pub nodes_expanded: u64,
+    pub elapsed_ms: f64,
+    // Résultat relu depuis le cache plutôt que recalculé
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub cached: bool,
+}
+
+#[derive(Serialize)]
+pub struct LegReport {
+    pub from: Coord,
+    pub to: Coord,
+    pub cost: i64,
+}
+
+#[derive(Serialize)]
+pub struct PathReport {
+    pub cost: i64,
+    pub length: usize,
+    pub optimal: bool,
+    pub path: Vec<Coord>,
+    pub step_costs: Vec<i64>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub legs: Vec<LegReport>,
+    pub solver: SolverInfo,
+}
+
+#[derive(Serialize)]
+pub struct AlternativeReport {
+    pub rank: usize,
+    pub overlap: f64,
+    #[serde(flatten)]
+    pub path: PathReport,
+}
+
+// Question « existe-t-il un chemin de coût entre min et max ? », exists vaut None si le budget de recherche est épuisé
+#[derive(Serialize)]
+pub struct CostRange {
+    pub min: i64,
+    pub max: i64,
+    pub exists: Option<bool>,
+}
+
+#[derive(Serialize)]
+pub struct Report {
+    pub rows: usize,
+    pub cols: usize,
+    pub start: Coord,
+    pub end: Coord,
+    pub via: Vec<Coord>,
+    pub topology: Topology,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_steps: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub budget: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub range: Option<CostRange>,
+    pub min: Option<PathReport>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub alternatives: Vec<AlternativeReport>,
+    pub max: Option<PathReport>,
+}
+
+#[derive(Serialize)]
+pub struct LayeredPathReport {
+    pub cost: i64,
+    pub length: usize,
+    // Couche (ou tranche de temps) et cellule de chaque pas
+    pub path: Vec<LayerCoord>,
+    pub step_costs: Vec<i64>,
+    pub solver: SolverInfo,
+}
+
+#[derive(Serialize)]
+pub struct LayeredReport {
+    pub rows: usize,
+    pub cols: usize,
+    pub layers: usize,
+    pub mode: LayerMode,
+    pub start: LayerCoord,
+    pub end: LayerCoord,
+    pub topology: Topology,
+    pub min: Option<LayeredPathReport>,
+}
+
+// Coût de chaque pas, le départ compte pour 0
+pub fn step_costs(path: &[Coord], grid: &Grid, movement: Movement) -> Vec<i64> {
+    std::iter::once(0)
+        .chain(path.windows(2).map(|pair| movement.step_cost(grid, pair[0], pair[1])))
+        .collect()
+}
+
+pub fn write_csv_report(report: &Report) {
+    println!("kind,rows,cols,start,end,cost,length,optimal,algorithm,nodes_expanded,elapsed_ms,path,step_costs");
+    let coord = |(r, c): Coord| format!("({},{})", r, c);
+    let alternatives = report.alternatives.iter().map(|alt| (format!("k{}", alt.rank), Some(&alt.path)));
+    let rows = [("min".to_string(), report.min.as_ref()), ("max".to_string(), report.max.as_ref())];
+    for (kind, result) in rows.into_iter().chain(alternatives) {
+        let Some(result) = result else { continue };
+        let path: Vec<String> = result.path.iter().map(|&p| coord(p)).collect();
+        let steps: Vec<String> = result.step_costs.iter().map(|c| c.to_string()).collect();
+        println!("{},{},{},\"{}\",\"{}\",{},{},{},{},{},{:.3},\"{}\",\"{}\"",
+                 kind, report.rows, report.cols, coord(report.start), coord(report.end),
+                 result.cost, result.length, result.optimal, result.solver.algorithm,
+                 result.solver.nodes_expanded, result.solver.elapsed_ms,
+                 path.join("->"), steps.join(";"));
+    }
+}
+
+pub fn write_bench_csv(report: &BenchReport) {
+    println!("size,algorithm,runs,solved,optimal,median_ms,p95_ms,median_nodes,p95_nodes,greedy_ratio_mean,greedy_ratio_min");
+    for size in &report.sizes {
+        let (mean, min) = match &size.greedy_ratio {
+            Some(ratio) => (format!("{:.4}", ratio.mean), format!("{:.4}", ratio.min)),
+            None => (String::new(), String::new()),
+        };
+        for timing in &size.solvers {
+            println!("{},{},{},{},{},{:.3},{:.3},{},{},{},{}",
+                     size.size, timing.algorithm, size.runs, timing.solved, timing.optimal,
+                     timing.median_ms, timing.p95_ms, timing.median_nodes, timing.p95_nodes, mean, min);
+        }
+    }
+}
+
+pub fn write_batch_csv(report: &BatchReport) {
+    println!("file,format,rows,cols,min_cost,min_length,min_algorithm,min_nodes,min_ms,max_cost,max_length,max_optimal,max_nodes,max_ms,error");
+    let quote = |text: &str| format!("\"{}\"", text.replace('"', "\"\""));
+    for result in &report.results {
+        let min = match &result.min {
+            Some(min) => format!("{},{},{},{},{:.3}", min.cost, min.length, min.algorithm, min.nodes_expanded, min.elapsed_ms),
+            None => ",,,,".to_string(),
+        };
+        let max = match &result.max {
+            Some(max) => format!("{},{},{},{},{:.3}", max.cost, max.length, max.optimal, max.nodes_expanded, max.elapsed_ms),
+            None => ",,,,".to_string(),
+        };
+        println!("{},{},{},{},{},{},{}", quote(&result.file), result.format.unwrap_or(""), result.rows, result.cols,
+                 min, max, result.error.as_deref().map(quote).unwrap_or_default());
+    }
+}
+
+pub fn write_verify_csv(verification: &Verification) {
+    println!("valid,length,cost,optimum,gap,gap_percent,issues");
+    let field = |value: Option<i64>| value.map(|v| v.to_string()).unwrap_or_default();
+    let issues: Vec<String> = verification.issues.iter()
+        .map(|issue| format!("step {}: {}", issue.step, issue.message))
+        .collect();
+    println!("{},{},{},{},{},{},\"{}\"", verification.valid, verification.length, field(verification.cost),
+             field(verification.optimum), field(verification.gap),
+             verification.gap_percent.map(|p| format!("{:.3}", p)).unwrap_or_default(), issues.join("; "));
+}
+
+// Une ligne par cellule modifiée, un mur laisse sa valeur vide
+pub fn write_diff_csv(diff: &MapDiff) {
+    println!("row,col,old,new,delta");
+    for change in &diff.changed {
+        let field = |value: Option<i64>| value.map(|v| v.to_string()).unwrap_or_default();
+        let delta = change.old.zip(change.new).map(|(old, new)| (new - old).to_string()).unwrap_or_default();
+        println!("{},{},{},{},{}", change.pos.0, change.pos.1, field(change.old), field(change.new), delta);
+    }
+}
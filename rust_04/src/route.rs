@@ -0,0 +1,254 @@
+use crate::grid::Grid;
+use crate::solver::{NegativeCycle, SearchObserver, Solver};
+use crate::Coord;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Leg {
+    pub from: Coord,
+    pub to: Coord,
+    pub cost: i64,
+    pub path: Vec<Coord>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Route {
+    pub legs: Vec<Leg>,
+    pub cost: i64,
+    pub path: Vec<Coord>,
+    pub nodes_expanded: u64,
+}
+
+// Au-delà, Held-Karp (2^k * k^2) devient trop coûteux et les étapes sont prises dans l'ordre donné
+pub const MAX_OPTIMIZED_WAYPOINTS: usize = 12;
+
+// Held-Karp sur les étapes intermédiaires, le départ et l'arrivée restent fixes.
+// `costs[i][j]` : coût de i vers j, 0 = départ, 1..=k = étapes, k + 1 = arrivée.
+fn best_waypoint_order(costs: &[Vec<Option<i64>>]) -> Option<Vec<usize>> {
+    let k = costs.len() - 2;
+    let end = k + 1;
+    if k == 0 {
+        return Some(Vec::new());
+    }
+
+    let full = (1usize << k) - 1;
+    let mut best = vec![vec![None::<(i64, usize)>; k]; 1 << k];
+
+    for i in 0..k {
+        if let Some(cost) = costs[0][i + 1] {
+            best[1 << i][i] = Some((cost, usize::MAX));
+        }
+    }
+
+    for mask in 1..=full {
+        for last in 0..k {
+            let Some((cost, _)) = best[mask][last] else { continue };
+            for next in 0..k {
+                if mask & (1 << next) != 0 {
+                    continue;
+                }
+                let Some(step) = costs[last + 1][next + 1] else { continue };
+                let entry = &mut best[mask | (1 << next)][next];
+                if entry.is_none_or(|(c, _)| cost.saturating_add(step) < c) {
+                    *entry = Some((cost.saturating_add(step), last));
+                }
+            }
+        }
+    }
+
+    let (_, mut last) = (0..k)
+        .filter_map(|i| {
+            let (cost, _) = best[full][i]?;
+            Some((cost.saturating_add(costs[i + 1][end]?), i))
+        })
+        .min()?;
+
+    let mut order = Vec::with_capacity(k);
+    let mut mask = full;
+    while last != usize::MAX {
+        order.push(last);
+        let (_, prev) = best[mask][last]?;
+        mask &= !(1 << last);
+        last = prev;
+    }
+    order.reverse();
+    Some(order)
+}
+
+/// Enchaîne les chemins minimaux de `start` à `end` par les étapes `via`, dans l'ordre donné ou le moins coûteux
+/// (jusqu'à `MAX_OPTIMIZED_WAYPOINTS` étapes). Une étape murée ou hors de la grille ne laisse aucun trajet ;
+/// un cycle négatif rencontré sur une étape est une erreur.
+pub fn plan_route(
+    solver: &dyn Solver,
+    grid: &Grid,
+    start: Coord,
+    end: Coord,
+    via: &[Coord],
+    optimize_order: bool,
+    observer: &mut dyn SearchObserver,
+) -> Result<Option<Route>, NegativeCycle> {
+    if [start, end].iter().chain(via).any(|&pos| !grid.contains(pos) || grid.is_wall(pos)) {
+        return Ok(None);
+    }
+    let mut nodes_expanded = 0;
+    let mut cycle = None;
+    let legs = {
+        let mut find = |from: Coord, to: Coord| match solver.try_solve(grid, from, to, observer) {
+            Ok(result) => {
+                let result = result?;
+                nodes_expanded += result.nodes_expanded;
+                Some((result.cost, result.path))
+            }
+            Err(found) => {
+                cycle.get_or_insert(found);
+                None
+            }
+        };
+        route_legs(&mut find, start, end, via, optimize_order)
+    };
+    if let Some(cycle) = cycle {
+        return Err(cycle);
+    }
+
+    Ok(legs.map(|legs| {
+        let mut path = vec![start];
+        for leg in &legs {
+            path.extend_from_slice(&leg.path[1..]);
+        }
+        let cost = legs.iter().map(|leg| leg.cost).sum();
+        Route { legs, cost, path, nodes_expanded }
+    }))
+}
+
+// Étapes du trajet, `find` donnant le coût et le chemin minimal entre deux cellules
+fn route_legs(
+    find: &mut impl FnMut(Coord, Coord) -> Option<(i64, Vec<Coord>)>,
+    start: Coord,
+    end: Coord,
+    via: &[Coord],
+    optimize_order: bool,
+) -> Option<Vec<Leg>> {
+    let mut stops = vec![start];
+    stops.extend_from_slice(via);
+    stops.push(end);
+
+    let mut legs = Vec::new();
+    if optimize_order && (2..=MAX_OPTIMIZED_WAYPOINTS).contains(&via.len()) {
+        // Coûts asymétriques : on entre dans la cellule d'arrivée, pas dans celle de départ
+        let mut results: Vec<Vec<_>> = stops.iter()
+            .map(|&from| stops.iter().map(|&to| find(from, to)).collect())
+            .collect();
+        let costs: Vec<Vec<Option<i64>>> = results.iter()
+            .map(|row| row.iter().map(|r| r.as_ref().map(|(c, _)| *c)).collect())
+            .collect();
+
+        let order = best_waypoint_order(&costs)?;
+        let mut sequence = vec![0];
+        sequence.extend(order.iter().map(|i| i + 1));
+        sequence.push(stops.len() - 1);
+
+        for pair in sequence.windows(2) {
+            let (cost, path) = results[pair[0]][pair[1]].take()?;
+            legs.push(Leg { from: stops[pair[0]], to: stops[pair[1]], cost, path });
+        }
+    } else {
+        for pair in stops.windows(2) {
+            let (cost, path) = find(pair[0], pair[1])?;
+            legs.push(Leg { from: pair[0], to: pair[1], cost, path });
+        }
+    }
+    Some(legs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::testing::{checked_cost, tiny_grid, MOVEMENTS};
+    use crate::solver::Dijkstra;
+    use rand::seq::SliceRandom;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn permutations(items: &[Coord]) -> Vec<Vec<Coord>> {
+        if items.is_empty() {
+            return vec![Vec::new()];
+        }
+        (0..items.len())
+            .flat_map(|i| {
+                let mut rest = items.to_vec();
+                let first = rest.remove(i);
+                permutations(&rest).into_iter().map(move |mut tail| {
+                    tail.insert(0, first);
+                    tail
+                })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn optimized_order_matches_every_permutation() {
+        for movement in MOVEMENTS {
+            let solver = Dijkstra { movement };
+            for seed in 0..12 {
+                let grid = tiny_grid(5, 6, 0.15, false, seed);
+                let mut rng = ChaCha8Rng::seed_from_u64(seed);
+                let mut open: Vec<Coord> = (1..grid.len() - 1).map(|i| grid.coord(i)).filter(|&pos| !grid.is_wall(pos)).collect();
+                open.shuffle(&mut rng);
+                let via = &open[..2 + seed as usize % 3];
+                let (start, end) = ((0, 0), (4, 5));
+
+                let expected = permutations(via).iter()
+                    .filter_map(|order| plan_route(&solver, &grid, start, end, order, false, &mut ()).unwrap())
+                    .map(|route| route.cost)
+                    .min();
+                let route = plan_route(&solver, &grid, start, end, via, true, &mut ()).unwrap();
+                assert_eq!(route.as_ref().map(|route| route.cost), expected, "{:?} via {:?}", movement, via);
+                let Some(route) = route else { continue };
+
+                // Chaque étape est visitée, les tronçons se suivent et le chemin recousu garde leur coût total
+                assert_eq!(route.legs.len(), via.len() + 1);
+                assert!(via.iter().all(|pos| route.legs.iter().any(|leg| leg.to == *pos)));
+                assert_eq!((route.legs[0].from, route.legs[via.len()].to), (start, end));
+                for (leg, next) in route.legs.iter().zip(&route.legs[1..]) {
+                    assert_eq!(leg.to, next.from);
+                }
+                for leg in &route.legs {
+                    assert_eq!(checked_cost(&grid, &leg.path, leg.from, leg.to, movement), leg.cost);
+                }
+                assert_eq!(route.path.len(), 1 + route.legs.iter().map(|leg| leg.path.len() - 1).sum::<usize>());
+                assert_eq!(route.cost, route.legs.iter().map(|leg| leg.cost).sum::<i64>());
+            }
+        }
+    }
+
+    #[test]
+    fn too_many_waypoints_keep_the_given_order() {
+        let grid = tiny_grid(4, 8, 0.0, false, 3);
+        let solver = Dijkstra { movement: MOVEMENTS[0] };
+        // Aller-retours d'un bord à l'autre : l'ordre donné n'est pas le meilleur
+        let via: Vec<Coord> = (0..=MAX_OPTIMIZED_WAYPOINTS).map(|i| (i % 4, if i % 2 == 0 { 7 } else { 0 })).collect();
+        let given = plan_route(&solver, &grid, (0, 0), (3, 7), &via, false, &mut ()).unwrap().unwrap();
+        let optimized = plan_route(&solver, &grid, (0, 0), (3, 7), &via, true, &mut ()).unwrap().unwrap();
+        assert_eq!(optimized, given);
+        let stops: Vec<Coord> = given.legs.iter().map(|leg| leg.to).collect();
+        assert_eq!(stops[..via.len()], via[..]);
+
+        let fewer = plan_route(&solver, &grid, (0, 0), (3, 7), &via[..MAX_OPTIMIZED_WAYPOINTS], true, &mut ()).unwrap().unwrap();
+        let in_order = plan_route(&solver, &grid, (0, 0), (3, 7), &via[..MAX_OPTIMIZED_WAYPOINTS], false, &mut ()).unwrap().unwrap();
+        assert!(fewer.cost < in_order.cost);
+    }
+
+    #[test]
+    fn walled_or_outside_waypoint_leaves_no_route() {
+        let mut grid = tiny_grid(3, 3, 0.0, false, 1);
+        grid.set((1, 1), None);
+        let solver = Dijkstra { movement: MOVEMENTS[0] };
+        for optimize_order in [false, true] {
+            for via in [vec![(1, 1)], vec![(0, 2), (1, 1)], vec![(0, 3)], vec![(5, 0), (2, 0)]] {
+                assert_eq!(plan_route(&solver, &grid, (0, 0), (2, 2), &via, optimize_order, &mut ()), Ok(None), "via {:?}", via);
+            }
+        }
+        assert_eq!(plan_route(&solver, &grid, (0, 0), (3, 3), &[], false, &mut ()), Ok(None));
+        assert!(plan_route(&solver, &grid, (0, 0), (2, 2), &[(0, 2), (2, 0)], true, &mut ()).unwrap().is_some());
+    }
+}
@@ -0,0 +1,120 @@
+use super::{SearchObserver, State};
+use crate::grid::Grid;
+use crate::topology::Movement;
+use crate::Coord;
+use clap::ValueEnum;
+use std::collections::BinaryHeap;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Heuristic {
+    /// Grid distance (Manhattan, Chebyshev or hex) times the cheapest step cost
+    Manhattan,
+    /// No estimate, A* then explores like Dijkstra
+    Zero,
+}
+
+impl Heuristic {
+    // Admissible et cohérente : chaque pas coûte au moins `min_cost`
+    pub fn estimate(self, from: Coord, to: Coord, min_cost: i64, movement: Movement) -> i64 {
+        match self {
+            Heuristic::Manhattan => movement.distance(from, to) as i64 * min_cost,
+            Heuristic::Zero => 0,
+        }
+    }
+}
+
+pub fn astar(grid: &Grid, start: Coord, end: Coord, heuristic: Heuristic, movement: Movement, observer: &mut dyn SearchObserver) -> Option<(i64, Vec<Coord>)> {
+    let index = |pos: Coord| grid.index(pos);
+
+    // Tableaux plats plutôt que des HashMap : les grilles sont denses
+    let mut dist = vec![i64::MAX; grid.len()];
+    let mut predecessors = vec![usize::MAX; grid.len()];
+    let mut heap = BinaryHeap::new();
+
+    // Avec des coûts négatifs l'estimation ne serait plus admissible
+    let min_cost = movement.min_step_cost(grid.min_value().unwrap_or(0).max(0));
+    let h = |pos: Coord| heuristic.estimate(pos, end, min_cost, movement);
+
+    dist[index(start)] = 0;
+    heap.push(State { cost: h(start), position: start });
+    observer.started(start);
+
+    while let Some(State { cost, position }) = heap.pop() {
+        let g = cost - h(position);
+
+        if position == end {
+            let mut path = vec![end];
+            let mut curr = index(end);
+            while curr != index(start) {
+                curr = predecessors[curr];
+                path.push(grid.coord(curr));
+            }
+            path.reverse();
+            return Some((g, path));
+        }
+
+        if g > dist[index(position)] {
+            continue;
+        }
+        observer.expanded(position);
+
+        for neighbor_pos in movement.neighbors(position, grid) {
+            let Some(new_g) = g.checked_add(movement.step_cost(grid, position, neighbor_pos)) else { continue };
+
+            if new_g < dist[index(neighbor_pos)] {
+                dist[index(neighbor_pos)] = new_g;
+                predecessors[index(neighbor_pos)] = index(position);
+                heap.push(State { cost: new_g.saturating_add(h(neighbor_pos)), position: neighbor_pos });
+                observer.discovered(neighbor_pos);
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate::{generate_map, Terrain};
+    use crate::solver::dijkstra;
+    use crate::solver::testing::{checked_cost, tiny_grid, MOVEMENTS};
+    use crate::topology::Topology;
+
+    #[test]
+    fn matches_dijkstra_on_random_grids() {
+        let movements = MOVEMENTS.into_iter().chain([Movement { topology: Topology::Eight, diagonal_cost: 0.5 }]);
+        for movement in movements {
+            for seed in 0..25 {
+                let grids = [tiny_grid(6, 7, 0.25, false, seed), generate_map(19, 13, 0.2, &[(0, 0), (12, 18)], &Terrain::default(), seed)];
+                for grid in &grids {
+                    let end = (grid.rows() - 1, grid.cols() - 1);
+                    for (start, end) in [((0, 0), end), (end, (grid.rows() / 2, 0))] {
+                        let expected = dijkstra(grid, start, end, movement, &mut ()).map(|(cost, _)| cost);
+                        for heuristic in [Heuristic::Manhattan, Heuristic::Zero] {
+                            let found = astar(grid, start, end, heuristic, movement, &mut ());
+                            assert_eq!(found.as_ref().map(|(cost, _)| *cost), expected, "{:?} {:?} seed {}", movement, heuristic, seed);
+                            if let Some((cost, path)) = found {
+                                assert_eq!(checked_cost(grid, &path, start, end, movement), cost);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn manhattan_estimate_never_overestimates() {
+        for movement in MOVEMENTS {
+            let grid = tiny_grid(6, 6, 0.0, false, 9);
+            let min_cost = movement.min_step_cost(grid.min_value().unwrap());
+            for i in 0..grid.len() {
+                let pos = grid.coord(i);
+                let exact = dijkstra(&grid, pos, (5, 5), movement, &mut ()).unwrap().0;
+                assert!(Heuristic::Manhattan.estimate(pos, (5, 5), min_cost, movement) <= exact);
+                assert_eq!(Heuristic::Zero.estimate(pos, (5, 5), min_cost, movement), 0);
+            }
+        }
+    }
+}
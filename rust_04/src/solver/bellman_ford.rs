@@ -0,0 +1,190 @@
+use super::SearchObserver;
+use crate::grid::Grid;
+use crate::topology::Movement;
+use crate::Coord;
+use std::collections::VecDeque;
+use std::fmt;
+
+/// Cycle de coût négatif atteignable : le coût minimal n'existe pas
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NegativeCycle {
+    pub cycle: Vec<Coord>,
+}
+
+impl fmt::Display for NegativeCycle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let cells: Vec<String> = self.cycle.iter().map(|(r, c)| format!("({},{})", r, c)).collect();
+        write!(f, "negative cycle through {}", cells.join("->"))
+    }
+}
+
+impl std::error::Error for NegativeCycle {}
+
+// Cherche un cycle dans le graphe des prédécesseurs ; il en existe un seulement
+// si un cycle négatif a été parcouru
+fn predecessor_cycle(predecessors: &[usize]) -> Option<Vec<usize>> {
+    const NONE: usize = usize::MAX;
+    let mut walk_id = vec![NONE; predecessors.len()];
+    for from in 0..predecessors.len() {
+        let mut curr = from;
+        while curr != NONE && walk_id[curr] == NONE {
+            walk_id[curr] = from;
+            curr = predecessors[curr];
+        }
+        if curr != NONE && walk_id[curr] == from {
+            let mut cycle = vec![curr];
+            let mut node = predecessors[curr];
+            while node != curr {
+                cycle.push(node);
+                node = predecessors[node];
+            }
+            cycle.reverse();
+            return Some(cycle);
+        }
+    }
+    None
+}
+
+/// Plus court chemin avec des coûts éventuellement négatifs (Bellman-Ford à file, SPFA).
+/// Le graphe des prédécesseurs est vérifié régulièrement pour détecter les cycles négatifs.
+pub fn bellman_ford(
+    grid: &Grid,
+    start: Coord,
+    end: Coord,
+    movement: Movement,
+    observer: &mut dyn SearchObserver,
+) -> Result<Option<(i64, Vec<Coord>)>, NegativeCycle> {
+    let n = grid.len();
+    let mut dist = vec![i64::MAX; n];
+    let mut predecessors = vec![usize::MAX; n];
+    let mut queued = vec![false; n];
+    let mut queue = VecDeque::new();
+    let mut relaxations = 0usize;
+
+    dist[grid.index(start)] = 0;
+    queue.push_back(grid.index(start));
+    queued[grid.index(start)] = true;
+    observer.started(start);
+
+    while let Some(current) = queue.pop_front() {
+        queued[current] = false;
+        let position = grid.coord(current);
+        observer.expanded(position);
+
+        for next in movement.neighbors(position, grid) {
+            let Some(total) = dist[current].checked_add(movement.step_cost(grid, position, next)) else { continue };
+            let i = grid.index(next);
+            if total >= dist[i] {
+                continue;
+            }
+            dist[i] = total;
+            predecessors[i] = current;
+            observer.discovered(next);
+            if !queued[i] {
+                queued[i] = true;
+                queue.push_back(i);
+            }
+
+            relaxations += 1;
+            if relaxations.is_multiple_of(n) {
+                if let Some(cycle) = predecessor_cycle(&predecessors) {
+                    return Err(NegativeCycle { cycle: cycle.into_iter().map(|i| grid.coord(i)).collect() });
+                }
+            }
+        }
+    }
+
+    let target = grid.index(end);
+    if dist[target] == i64::MAX {
+        return Ok(None);
+    }
+    let mut path = vec![end];
+    let mut curr = target;
+    while curr != grid.index(start) {
+        curr = predecessors[curr];
+        path.push(grid.coord(curr));
+    }
+    path.reverse();
+    Ok(Some((dist[target], path)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::{parse_map, CellWidth, ParseOptions};
+    use crate::solver::testing::{checked_cost, simple_paths, tiny_grid, MOVEMENTS};
+    use crate::topology::Topology;
+
+    // Rend négatives des cellules dont tous les voisins sont positifs et au moins aussi grands en valeur absolue :
+    // chaque pas vers une récompense est alors compensé par le pas suivant, aucun cycle n'est négatif
+    fn rewards_without_cycle(grid: &Grid, movement: Movement) -> Grid {
+        let mut grid = grid.clone();
+        for i in (0..grid.len()).step_by(3) {
+            let pos = grid.coord(i);
+            let Some(value) = grid.cell(pos) else { continue };
+            let smallest = movement.neighbors(pos, &grid).map(|next| grid.cell(next).unwrap_or(0)).min().unwrap_or(0);
+            if smallest > 0 && value > 0 {
+                grid.set(pos, Some(-value.min(smallest)));
+            }
+        }
+        grid.with_cell_type(CellWidth::U8, true)
+    }
+
+    #[test]
+    fn negative_cells_without_cycle_match_exhaustive_enumeration() {
+        let mut with_rewards = 0;
+        for movement in MOVEMENTS.into_iter().filter(|m| m.diagonal_cost == 1.0) {
+            for (rows, cols) in [(2, 2), (3, 3), (3, 4), (4, 3)] {
+                for seed in 0..15 {
+                    let grid = rewards_without_cycle(&tiny_grid(rows, cols, 0.2, false, seed), movement);
+                    with_rewards += grid.has_negative() as usize;
+                    let (start, end) = ((0, 0), (rows - 1, cols - 1));
+                    let expected = simple_paths(&grid, start, end, movement).into_iter().map(|(cost, _)| cost).min();
+                    let found = bellman_ford(&grid, start, end, movement, &mut ()).unwrap();
+                    assert_eq!(found.as_ref().map(|(cost, _)| *cost), expected, "{:?} {:?}", movement, grid);
+                    if let Some((cost, path)) = found {
+                        assert_eq!(checked_cost(&grid, &path, start, end, movement), cost);
+                    }
+                }
+            }
+        }
+        assert!(with_rewards > 50, "only {} grids with negative cells", with_rewards);
+    }
+
+    #[test]
+    fn reward_next_to_a_smaller_cost_is_a_two_cell_cycle() {
+        let options = ParseOptions { signed: true, ..ParseOptions::default() };
+        let grid = parse_map("01 FE\n01 01\n", options).unwrap();
+        let movement = MOVEMENTS[0];
+        let cycle = bellman_ford(&grid, (0, 0), (1, 1), movement, &mut ()).unwrap_err().cycle;
+        assert_eq!(cycle.len(), 2);
+        assert!(cycle.contains(&(0, 1)));
+        // Le même coût autour de la récompense l'annule exactement
+        let grid = parse_map("02 FE\n02 02\n", options).unwrap();
+        assert_eq!(bellman_ford(&grid, (0, 0), (1, 1), movement, &mut ()), Ok(Some((0, vec![(0, 0), (0, 1), (1, 1)]))));
+    }
+
+    #[test]
+    fn longer_cycle_is_detected_when_every_back_and_forth_is_positive() {
+        // Le pas diagonal triple la valeur de la récompense : (0,0) -> (0,1) -> (1,1) -> (0,0) coûte 4 + 4 - 12
+        let grid = Grid::new(2, 2, vec![Some(-4), Some(4), None, Some(4)]).with_cell_type(CellWidth::U8, true);
+        let movement = Movement { topology: Topology::Eight, diagonal_cost: 3.0 };
+        let mut cycle = bellman_ford(&grid, (0, 0), (1, 1), movement, &mut ()).unwrap_err().cycle;
+        cycle.sort();
+        assert_eq!(cycle, [(0, 0), (0, 1), (1, 1)]);
+        assert_eq!(
+            NegativeCycle { cycle: vec![(0, 1), (0, 0)] }.to_string(),
+            "negative cycle through (0,1)->(0,0)"
+        );
+    }
+
+    #[test]
+    fn unreachable_end_has_no_path() {
+        let grid = Grid::new(3, 3, vec![Some(1), None, Some(1), Some(-1), None, Some(1), Some(2), None, Some(1)])
+            .with_cell_type(CellWidth::U8, true);
+        for movement in MOVEMENTS.into_iter().filter(|m| m.topology == Topology::Four) {
+            assert_eq!(bellman_ford(&grid, (0, 0), (2, 2), movement, &mut ()), Ok(None));
+        }
+        assert_eq!(bellman_ford(&grid, (0, 0), (2, 0), MOVEMENTS[0], &mut ()), Ok(Some((1, vec![(0, 0), (1, 0), (2, 0)]))));
+    }
+}
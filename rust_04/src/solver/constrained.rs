@@ -0,0 +1,94 @@
+use super::SearchObserver;
+use crate::grid::Grid;
+use crate::topology::Movement;
+use crate::Coord;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+// Chemin partiel, remonté par `parent` ; son coût et son nombre de pas sont gardés dans la file
+struct Label {
+    position: Coord,
+    parent: Option<usize>,
+}
+
+/// Chemin minimal d'au plus `max_steps` pas, coûts positifs uniquement.
+/// Recherche à étiquettes (plus court chemin sous contrainte de ressource) : une cellule garde tout chemin
+/// partiel qui n'est dominé par aucun autre, c'est-à-dire plus court en pas que tous ceux moins chers déjà développés.
+pub fn step_limited_path(grid: &Grid, start: Coord, end: Coord, movement: Movement, max_steps: usize, observer: &mut dyn SearchObserver) -> Option<(i64, Vec<Coord>)> {
+    let mut labels = vec![Label { position: start, parent: None }];
+    // Nombre de pas du chemin développé le plus court de chaque cellule
+    let mut fewest_steps = vec![usize::MAX; grid.len()];
+    let mut heap = BinaryHeap::new();
+    heap.push(Reverse((0i64, 0usize, 0usize)));
+    observer.started(start);
+
+    while let Some(Reverse((cost, steps, label))) = heap.pop() {
+        let position = labels[label].position;
+        if position == end {
+            let mut path = vec![position];
+            let mut curr = label;
+            while let Some(parent) = labels[curr].parent {
+                path.push(labels[parent].position);
+                curr = parent;
+            }
+            path.reverse();
+            return Some((cost, path));
+        }
+
+        // Les étiquettes sortent par coût croissant : un chemin déjà développé ici coûtait moins et comptait moins de pas
+        let index = grid.index(position);
+        if steps >= fewest_steps[index] {
+            continue;
+        }
+        fewest_steps[index] = steps;
+        observer.expanded(position);
+
+        for next in movement.neighbors(position, grid) {
+            // Même en ligne droite, la fin ne serait plus atteignable à temps
+            if steps + 1 + movement.distance(next, end) as usize > max_steps || steps + 1 >= fewest_steps[grid.index(next)] {
+                continue;
+            }
+            let Some(total) = cost.checked_add(movement.step_cost(grid, position, next)) else { continue };
+            labels.push(Label { position: next, parent: Some(label) });
+            heap.push(Reverse((total, steps + 1, labels.len() - 1)));
+            observer.discovered(next);
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::testing::{checked_cost, simple_paths, tiny_grid, MOVEMENTS};
+
+    #[test]
+    fn matches_exhaustive_enumeration() {
+        for movement in MOVEMENTS {
+            for (rows, cols) in [(2, 3), (3, 3), (3, 4)] {
+                for seed in 0..8 {
+                    let grid = tiny_grid(rows, cols, if seed % 2 == 0 { 0.0 } else { 0.25 }, false, seed);
+                    let (start, end) = ((0, 0), (rows - 1, cols - 1));
+                    let paths = simple_paths(&grid, start, end, movement);
+                    for max_steps in 0..rows * cols {
+                        let expected = paths.iter().filter(|(_, path)| path.len() - 1 <= max_steps).map(|(cost, _)| *cost).min();
+                        let found = step_limited_path(&grid, start, end, movement, max_steps, &mut ());
+                        assert_eq!(found.as_ref().map(|(cost, _)| *cost), expected,
+                                   "{:?} {}x{} seed {} max {} steps", movement, rows, cols, seed, max_steps);
+                        if let Some((cost, path)) = found {
+                            assert!(path.len() - 1 <= max_steps);
+                            assert_eq!(checked_cost(&grid, &path, start, end, movement), cost);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn start_is_the_end() {
+        let grid = tiny_grid(2, 2, 0.0, false, 0);
+        assert_eq!(step_limited_path(&grid, (1, 1), (1, 1), MOVEMENTS[0], 0, &mut ()), Some((0, vec![(1, 1)])));
+    }
+}
@@ -0,0 +1,177 @@
+use super::SearchObserver;
+use crate::grid::Grid;
+use crate::topology::Movement;
+use crate::Coord;
+use std::collections::BinaryHeap;
+
+/// Coût de pas maximal accepté par la file à seaux, au-delà elle aurait trop de seaux
+pub const MAX_DIAL_STEP: i64 = 4095;
+
+/// Coût de pas maximal de la grille si `dial_dijkstra` s'applique : pas de coût négatif,
+/// pas plus grand que `MAX_DIAL_STEP`, et toute distance tient sur 32 bits.
+pub fn dial_max_step(grid: &Grid, movement: Movement) -> Option<u32> {
+    if grid.min_value()? < 0 || grid.len() > u32::MAX as usize {
+        return None;
+    }
+    let max_step = movement.max_step_cost(grid.max_value()?);
+    // Un plus court chemin est simple : il coûte au plus une cellule de chaque
+    if max_step > MAX_DIAL_STEP || grid.len() as u64 * max_step as u64 > u32::MAX as u64 {
+        return None;
+    }
+    Some(max_step as u32)
+}
+
+/// Dijkstra sur tableaux plats avec une file à seaux circulaire (Dial), pour les grandes cartes.
+/// Donne exactement le résultat de `dijkstra` : à coût égal, la plus grande position sort d'abord.
+/// Renvoie None si aucun chemin n'existe ou si `dial_max_step` refuse la grille.
+pub fn dial_dijkstra(grid: &Grid, start: Coord, end: Coord, movement: Movement, observer: &mut dyn SearchObserver) -> Option<(i64, Vec<Coord>)> {
+    let max_step = dial_max_step(grid, movement)? as usize;
+    let n = grid.len();
+    let mut dist = vec![u32::MAX; n];
+    let mut predecessors = vec![u32::MAX; n];
+    // Le seau `d % slots` contient les positions de coût provisoire `d` ; tous les coûts en attente
+    // sont dans `cost + 1..=cost + max_step`, donc deux coûts différents ne partagent jamais un seau
+    let slots = max_step + 1;
+    let mut buckets: Vec<Vec<u32>> = vec![Vec::new(); slots];
+    let mut pending = 0usize;
+    // Positions au coût courant, la plus grande d'abord comme dans le tas binaire de `dijkstra`
+    let mut current = BinaryHeap::new();
+    let mut cost = 0u32;
+
+    let (source, target) = (grid.index(start), grid.index(end));
+    dist[source] = 0;
+    current.push(source as u32);
+    observer.started(start);
+
+    loop {
+        let Some(index) = current.pop() else {
+            if pending == 0 {
+                return None;
+            }
+            let slot = loop {
+                cost += 1;
+                let slot = cost as usize % slots;
+                if !buckets[slot].is_empty() {
+                    break slot;
+                }
+            };
+            pending -= buckets[slot].len();
+            current = BinaryHeap::from(std::mem::take(&mut buckets[slot]));
+            continue;
+        };
+        let index = index as usize;
+        if index == target {
+            let mut path = vec![end];
+            let mut curr = target;
+            while curr != source {
+                curr = predecessors[curr] as usize;
+                path.push(grid.coord(curr));
+            }
+            path.reverse();
+            return Some((cost as i64, path));
+        }
+        if cost > dist[index] {
+            continue;
+        }
+        let position = grid.coord(index);
+        observer.expanded(position);
+
+        for next in movement.neighbors(position, grid) {
+            let step = movement.step_cost(grid, position, next) as u32;
+            let total = cost + step;
+            let i = grid.index(next);
+            if total < dist[i] {
+                dist[i] = total;
+                predecessors[i] = index as u32;
+                if step == 0 {
+                    current.push(i as u32);
+                } else {
+                    buckets[total as usize % slots].push(i as u32);
+                    pending += 1;
+                }
+                observer.discovered(next);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate::{generate_map, Terrain};
+    use crate::grid::CellWidth;
+    use crate::solver::{dijkstra, Dijkstra, SearchStats, Solver, DIAL_MIN_CELLS};
+    use crate::topology::Topology;
+
+    fn assert_same(grid: &Grid, start: Coord, end: Coord, movement: Movement) {
+        let mut heap_stats = SearchStats::default();
+        let mut dial_stats = SearchStats::default();
+        let expected = dijkstra(grid, start, end, movement, &mut heap_stats);
+        let found = dial_dijkstra(grid, start, end, movement, &mut dial_stats);
+        assert_eq!(found, expected, "{:?} from {:?} to {:?}", movement, start, end);
+        assert_eq!(dial_stats.nodes_expanded, heap_stats.nodes_expanded);
+    }
+
+    #[test]
+    fn matches_binary_heap_dijkstra() {
+        for topology in [Topology::Four, Topology::Eight, Topology::Hex] {
+            for diagonal_cost in [1.0, 0.5, 1.5] {
+                let movement = Movement { topology, diagonal_cost };
+                for seed in 0..8 {
+                    let wall_density = if seed % 2 == 0 { 0.0 } else { 0.3 };
+                    let grid = generate_map(23, 17, wall_density, &[(0, 0), (16, 22)], &Terrain::default(), seed);
+                    assert_same(&grid, (0, 0), (16, 22), movement);
+                    assert_same(&grid, (16, 22), (3, 5), movement);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn matches_with_zero_cost_ties() {
+        // Beaucoup de cellules à 0 et de coûts égaux : l'ordre de sortie décide du chemin
+        let rows = (0..20)
+            .map(|r| (0..20).map(|c| Some(((r * 7 + c * 3) % 5 / 2) as i64)).collect())
+            .collect();
+        let grid = Grid::from_rows(rows);
+        for topology in [Topology::Four, Topology::Eight, Topology::Hex] {
+            let movement = Movement { topology, diagonal_cost: 1.0 };
+            assert_same(&grid, (0, 0), (19, 19), movement);
+            assert_same(&grid, (19, 0), (0, 19), movement);
+        }
+    }
+
+    #[test]
+    fn unreachable_end() {
+        let grid = Grid::from_rows(vec![
+            vec![Some(1), Some(2), None],
+            vec![Some(3), None, Some(4)],
+            vec![None, Some(5), Some(6)],
+        ]);
+        let movement = Movement { topology: Topology::Four, diagonal_cost: 1.0 };
+        assert_eq!(dial_dijkstra(&grid, (0, 0), (2, 2), movement, &mut ()), None);
+        assert_same(&grid, (0, 0), (2, 2), movement);
+    }
+
+    #[test]
+    fn rejects_negative_and_large_costs() {
+        let movement = Movement { topology: Topology::Four, diagonal_cost: 1.0 };
+        let signed = Grid::from_rows(vec![vec![Some(1), Some(-2)]]).with_cell_type(CellWidth::U8, true);
+        assert_eq!(dial_max_step(&signed, movement), None);
+        let wide = Grid::from_rows(vec![vec![Some(1), Some(0x2000)]]).with_cell_type(CellWidth::U16, false);
+        assert_eq!(dial_max_step(&wide, movement), None);
+        let diagonal = Movement { topology: Topology::Eight, diagonal_cost: 2.0 };
+        assert_eq!(dial_max_step(&Grid::from_rows(vec![vec![Some(0xFF)]]), diagonal), Some(510));
+    }
+
+    #[test]
+    fn dijkstra_solver_is_unchanged_on_large_maps() {
+        let grid = generate_map(300, 300, 0.2, &[(0, 0), (299, 299)], &Terrain::default(), 42);
+        assert!(grid.len() >= DIAL_MIN_CELLS);
+        let movement = Movement { topology: Topology::Four, diagonal_cost: 1.0 };
+        let mut stats = SearchStats::default();
+        let (cost, path) = dijkstra(&grid, (0, 0), (299, 299), movement, &mut stats).unwrap();
+        let result = Dijkstra { movement }.solve(&grid, (0, 0), (299, 299), &mut ()).unwrap();
+        assert_eq!((result.cost, result.path, result.nodes_expanded), (cost, path, stats.nodes_expanded));
+    }
+}
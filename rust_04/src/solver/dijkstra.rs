@@ -0,0 +1,53 @@
+use super::{SearchObserver, State};
+use crate::grid::Grid;
+use crate::topology::Movement;
+use crate::Coord;
+use std::collections::{BinaryHeap, HashMap};
+
+pub fn dijkstra(grid: &Grid, start: Coord, end: Coord, movement: Movement, observer: &mut dyn SearchObserver) -> Option<(i64, Vec<Coord>)> {
+    let mut dist: HashMap<Coord, i64> = HashMap::new();
+    let mut predecessors: HashMap<Coord, Coord> = HashMap::new();
+    let mut heap = BinaryHeap::new();
+
+    dist.insert(start, 0);
+    heap.push(State { cost: 0, position: start });
+    observer.started(start);
+
+    while let Some(State { cost, position }) = heap.pop() {
+        if position == end {
+            let total_cost = dist[&end];
+            let mut path = Vec::new();
+            let mut curr = end;
+            
+            while curr != start {
+                path.push(curr);
+                curr = predecessors[&curr];
+            }
+            path.push(start);
+            path.reverse();
+            return Some((total_cost, path));
+        }
+
+        if cost > dist[&position] {
+            continue;
+        }
+        observer.expanded(position);
+
+        for neighbor_pos in movement.neighbors(position, grid) {
+            let step_cost = movement.step_cost(grid, position, neighbor_pos);
+            // Un total qui déborde ne peut pas être le plus court
+            let Some(new_total_cost) = cost.checked_add(step_cost) else { continue };
+
+            let current_dist = dist.get(&neighbor_pos).copied().unwrap_or(i64::MAX);
+            
+            if new_total_cost < current_dist {
+                dist.insert(neighbor_pos, new_total_cost);
+                predecessors.insert(neighbor_pos, position);
+                heap.push(State { cost: new_total_cost, position: neighbor_pos });
+                observer.discovered(neighbor_pos);
+            }
+        }
+    }
+
+    None
+}
@@ -0,0 +1,402 @@
+use super::PathResult;
+use crate::grid::Grid;
+use crate::topology::{Movement, Topology};
+use crate::Coord;
+use std::time::{Duration, Instant};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SearchBudget {
+    pub max_nodes: u64,
+    pub time_limit: Option<Duration>,
+}
+
+// Branch-and-bound exact pour le plus long chemin simple
+struct MaxPathSearch<'a> {
+    grid: &'a Grid,
+    end: Coord,
+    movement: Movement,
+    budget: SearchBudget,
+    // Coût à ne pas dépasser, et coût à partir duquel un chemin suffit
+    limit: Option<i64>,
+    stop_at: Option<i64>,
+    // Sans cellule négative, un chemin qui dépasse `limit` ne peut plus redescendre
+    non_negative: bool,
+    started: Instant,
+    visited: Vec<Vec<bool>>,
+    path: Vec<Coord>,
+    best_cost: Option<i64>,
+    best_path: Vec<Coord>,
+    nodes: u64,
+    exhausted: bool,
+}
+
+impl MaxPathSearch<'_> {
+    fn neighbors(&self, pos: Coord) -> impl Iterator<Item = Coord> + '_ {
+        self.movement.neighbors(pos, self.grid)
+    }
+
+    fn within_limit(&self, cost: i64) -> bool {
+        self.limit.is_none_or(|limit| cost <= limit)
+    }
+
+    fn target_reached(&self) -> bool {
+        self.stop_at.is_some_and(|target| self.best_cost.is_some_and(|best| best >= target))
+    }
+
+    fn out_of_budget(&mut self) -> bool {
+        if self.nodes >= self.budget.max_nodes {
+            self.exhausted = true;
+        }
+        // Chaque nœud parcourt déjà la grille pour sa borne, l'horloge est négligeable à côté
+        if let Some(limit) = self.budget.time_limit {
+            if self.started.elapsed() >= limit {
+                self.exhausted = true;
+            }
+        }
+        self.exhausted
+    }
+
+    // Borne supérieure : somme des cellules encore atteignables depuis `pos`.
+    // Les culs-de-sac (sauf la fin) ne peuvent pas être traversés, on les ignore.
+    // Renvoie None si la fin n'est plus atteignable.
+    fn upper_bound(&self, pos: Coord) -> Option<i64> {
+        let rows = self.grid.rows();
+        let cols = self.grid.cols();
+        let mut reachable = vec![vec![false; cols]; rows];
+        let mut stack = vec![pos];
+        let mut cells = Vec::new();
+        let mut end_reached = false;
+
+        while let Some(cur) = stack.pop() {
+            for next in self.neighbors(cur) {
+                if self.visited[next.0][next.1] || reachable[next.0][next.1] {
+                    continue;
+                }
+                reachable[next.0][next.1] = true;
+                cells.push(next);
+                if next == self.end {
+                    end_reached = true;
+                } else {
+                    stack.push(next);
+                }
+            }
+        }
+
+        if !end_reached {
+            return None;
+        }
+
+        let mut values = Vec::new();
+        for &cell in &cells {
+            let degree = self.neighbors(cell)
+                .filter(|&n| n == pos || reachable[n.0][n.1])
+                .count();
+            if cell == self.end || degree >= 2 {
+                // Une cellule négative n'augmente jamais le coût, elle compte pour 0
+                values.push((cell, self.movement.max_step_cost(self.grid.cell(cell).unwrap_or(0).max(0))));
+            }
+        }
+
+        if self.movement.topology != Topology::Four {
+            return Some(values.iter().map(|&(_, v)| v).sum());
+        }
+
+        // Grille 4-voisins bipartite : le chemin alterne les couleurs (r + c) % 2,
+        // donc le nombre de cellules utilisables de chaque couleur est contraint
+        let color = |(r, c): Coord| (r + c) % 2;
+        let (same, other): (Vec<_>, Vec<_>) = values.iter().partition(|&&(cell, _)| color(cell) == color(pos));
+        let mut same: Vec<i64> = same.iter().map(|&(_, v)| v).collect();
+        let mut other: Vec<i64> = other.iter().map(|&(_, v)| v).collect();
+
+        let extra = usize::from(color(self.end) != color(pos));
+        let take_same = same.len().min(other.len().saturating_sub(extra));
+        let take_other = (take_same + extra).min(other.len());
+        same.sort_unstable_by(|a, b| b.cmp(a));
+        other.sort_unstable_by(|a, b| b.cmp(a));
+        Some(same[..take_same].iter().sum::<i64>() + other[..take_other].iter().sum::<i64>())
+    }
+
+    // Compte le nœud et renvoie ses successeurs à explorer, ou None s'il est terminal ou élagué
+    fn enter(&mut self, pos: Coord, cur_cost: i64) -> Option<Vec<Coord>> {
+        self.nodes += 1;
+        if self.out_of_budget() {
+            return None;
+        }
+
+        if pos == self.end {
+            if self.within_limit(cur_cost) && self.best_cost.is_none_or(|best| cur_cost > best) {
+                self.best_cost = Some(cur_cost);
+                self.best_path = self.path.clone();
+            }
+            return None;
+        }
+        if self.non_negative && !self.within_limit(cur_cost) {
+            return None;
+        }
+
+        // Aucun chemin ne peut rapporter plus que `limit`
+        let bound = self.upper_bound(pos)?;
+        let reachable = cur_cost.saturating_add(bound).min(self.limit.unwrap_or(i64::MAX));
+        if self.best_cost.is_some_and(|best| reachable <= best) {
+            return None;
+        }
+
+        // Règle de Warnsdorff (cellules les moins accessibles d'abord), puis les plus chères
+        let mut next: Vec<Coord> = self.neighbors(pos)
+            .filter(|&(r, c)| !self.visited[r][c])
+            .collect();
+        next.sort_by_key(|&(r, c)| {
+            let onward = self.neighbors((r, c)).filter(|&(nr, nc)| !self.visited[nr][nc]).count();
+            (onward, std::cmp::Reverse(self.grid.cell((r, c))))
+        });
+        Some(next)
+    }
+
+    fn leave(&mut self, (r, c): Coord) {
+        self.path.pop();
+        self.visited[r][c] = false;
+    }
+
+    // Parcours en profondeur avec une pile explicite : un chemin peut couvrir toute la grille
+    fn dfs(&mut self, start: Coord) {
+        let Some(next) = self.enter(start, 0) else { return };
+        let mut stack = vec![Frame { pos: start, cost: 0, next, index: 0 }];
+
+        while let Some(frame) = stack.last_mut() {
+            if self.exhausted || self.target_reached() {
+                return;
+            }
+            let Some(&child) = frame.next.get(frame.index) else {
+                let pos = frame.pos;
+                stack.pop();
+                if !stack.is_empty() {
+                    self.leave(pos);
+                }
+                continue;
+            };
+            frame.index += 1;
+            let (pos, cost) = (frame.pos, frame.cost);
+
+            self.visited[child.0][child.1] = true;
+            self.path.push(child);
+            let cost = cost.saturating_add(self.movement.step_cost(self.grid, pos, child));
+            match self.enter(child, cost) {
+                Some(next) => stack.push(Frame { pos: child, cost, next, index: 0 }),
+                None => self.leave(child),
+            }
+        }
+    }
+}
+
+struct Frame {
+    pos: Coord,
+    cost: i64,
+    next: Vec<Coord>,
+    index: usize,
+}
+
+pub fn max_path_dfs(grid: &Grid, start: Coord, end: Coord, movement: Movement, budget: SearchBudget) -> Option<PathResult> {
+    capped_max_path(grid, start, end, movement, budget, None, None)
+}
+
+/// Chemin maximal dont le coût ne dépasse pas `limit`. La recherche s'arrête dès qu'un chemin atteint
+/// `stop_at` ; le résultat n'est alors pas marqué optimal, mais il répond à « existe-t-il un chemin
+/// de coût compris entre `stop_at` et `limit` ? ».
+pub fn capped_max_path(grid: &Grid, start: Coord, end: Coord, movement: Movement, budget: SearchBudget,
+                       limit: Option<i64>, stop_at: Option<i64>) -> Option<PathResult> {
+    let rows = grid.rows();
+    let cols = grid.cols();
+
+    let mut search = MaxPathSearch {
+        grid,
+        end,
+        movement,
+        budget,
+        limit,
+        stop_at,
+        non_negative: !grid.has_negative(),
+        started: Instant::now(),
+        visited: vec![vec![false; cols]; rows],
+        path: vec![start],
+        best_cost: None,
+        best_path: Vec::new(),
+        nodes: 0,
+        exhausted: false,
+    };
+
+    // L'heuristique glouton fournit une première borne inférieure
+    if let Some((cost, path)) = greedy_max_path(grid, start, end, movement).filter(|&(cost, _)| search.within_limit(cost)) {
+        search.best_cost = Some(cost);
+        search.best_path = path;
+    }
+
+    search.visited[start.0][start.1] = true;
+    if !search.target_reached() {
+        search.dfs(start);
+    }
+
+    let optimal = !search.exhausted && !search.target_reached();
+    let nodes_expanded = search.nodes;
+    search.best_cost.map(|cost| PathResult { cost, path: search.best_path, optimal, nodes_expanded })
+}
+
+// Heuristique glouton pour trouver un chemin de coût élevé (pas exhaustif)
+pub fn greedy_max_path(grid: &Grid, start: Coord, end: Coord, movement: Movement) -> Option<(i64, Vec<Coord>)> {
+    let rows = grid.rows();
+    let cols = grid.cols();
+    
+    let mut visited = vec![vec![false; cols]; rows];
+    let mut path = vec![start];
+    let mut cost = 0i64;
+    let mut current = start;
+    
+    visited[start.0][start.1] = true;
+
+    // Explore greedily vers les cellules de plus haute valeur
+    while current != end {
+        let mut best_next = None;
+        let mut best_value = 0i64;
+        
+        for (nr, nc) in movement.neighbors(current, grid) {
+            if !visited[nr][nc] {
+                let cell_value = grid.cell((nr, nc)).unwrap_or(0);
+                if cell_value > best_value {
+                    best_value = cell_value;
+                    best_next = Some((nr, nc));
+                }
+            }
+        }
+        
+        match best_next {
+            Some((nr, nc)) => {
+                visited[nr][nc] = true;
+                cost = cost.saturating_add(movement.step_cost(grid, current, (nr, nc)));
+                path.push((nr, nc));
+                current = (nr, nc);
+            }
+            None => {
+                // Si bloqué, chercher n'importe quel chemin non visité
+                let mut found = false;
+                for (nr, nc) in movement.neighbors(current, grid) {
+                    if !visited[nr][nc] {
+                        visited[nr][nc] = true;
+                        cost = cost.saturating_add(movement.step_cost(grid, current, (nr, nc)));
+                        path.push((nr, nc));
+                        current = (nr, nc);
+                        found = true;
+                        break;
+                    }
+                }
+                if !found { break; }
+            }
+        }
+    }
+
+    if current == end {
+        Some((cost, path))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::testing::{checked_cost, simple_paths, tiny_grid, MOVEMENTS};
+
+    const UNLIMITED: SearchBudget = SearchBudget { max_nodes: u64::MAX, time_limit: None };
+
+    #[test]
+    fn matches_exhaustive_enumeration() {
+        for movement in MOVEMENTS {
+            for (rows, cols) in [(1, 5), (2, 3), (3, 3), (3, 4)] {
+                for seed in 0..12 {
+                    let grid = tiny_grid(rows, cols, if seed % 3 == 0 { 0.0 } else { 0.25 }, seed % 2 == 1, seed);
+                    let (start, end) = ((0, 0), (rows - 1, cols - 1));
+                    let expected = simple_paths(&grid, start, end, movement).into_iter().map(|(cost, _)| cost).max();
+                    let found = max_path_dfs(&grid, start, end, movement, UNLIMITED);
+                    assert_eq!(found.as_ref().map(|result| result.cost), expected, "{:?} {}x{} seed {}", movement, rows, cols, seed);
+                    if let Some(result) = found {
+                        assert!(result.optimal);
+                        assert_eq!(checked_cost(&grid, &result.path, start, end, movement), result.cost);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn greedy_path_is_valid_and_never_better() {
+        for movement in MOVEMENTS {
+            for seed in 0..12 {
+                let grid = tiny_grid(3, 4, 0.2, seed % 2 == 1, seed);
+                let (start, end) = ((0, 0), (2, 3));
+                let best = simple_paths(&grid, start, end, movement).into_iter().map(|(cost, _)| cost).max();
+                if let Some((cost, path)) = greedy_max_path(&grid, start, end, movement) {
+                    assert_eq!(checked_cost(&grid, &path, start, end, movement), cost);
+                    assert!(Some(cost) <= best, "{:?} seed {}", movement, seed);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn node_budget_marks_result_as_not_optimal() {
+        let movement = MOVEMENTS[0];
+        let grid = tiny_grid(5, 5, 0.0, false, 1);
+        let result = max_path_dfs(&grid, (0, 0), (4, 4), movement, SearchBudget { max_nodes: 10, time_limit: None }).unwrap();
+        assert!(!result.optimal);
+        assert!(result.nodes_expanded <= 11);
+        assert_eq!(checked_cost(&grid, &result.path, (0, 0), (4, 4), movement), result.cost);
+    }
+
+    #[test]
+    fn limit_keeps_the_best_path_under_it() {
+        for movement in MOVEMENTS {
+            for seed in 0..10 {
+                let grid = tiny_grid(3, 3, 0.1, seed % 2 == 1, seed);
+                let (start, end) = ((0, 0), (2, 2));
+                let costs: Vec<i64> = simple_paths(&grid, start, end, movement).into_iter().map(|(cost, _)| cost).collect();
+                let (Some(&lowest), Some(&highest)) = (costs.iter().min(), costs.iter().max()) else { continue };
+                for limit in lowest - 1..=highest + 1 {
+                    let expected = costs.iter().copied().filter(|&cost| cost <= limit).max();
+                    let found = capped_max_path(&grid, start, end, movement, UNLIMITED, Some(limit), None);
+                    assert_eq!(found.as_ref().map(|result| result.cost), expected, "{:?} seed {} limit {}", movement, seed, limit);
+                    if let Some(result) = found {
+                        assert!(result.optimal);
+                        assert_eq!(checked_cost(&grid, &result.path, start, end, movement), result.cost);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn stop_at_answers_whether_a_cost_is_in_range() {
+        for movement in MOVEMENTS {
+            for seed in 0..10 {
+                let grid = tiny_grid(3, 3, 0.1, seed % 2 == 1, seed);
+                let (start, end) = ((0, 0), (2, 2));
+                let costs: Vec<i64> = simple_paths(&grid, start, end, movement).into_iter().map(|(cost, _)| cost).collect();
+                let (Some(&lowest), Some(&highest)) = (costs.iter().min(), costs.iter().max()) else { continue };
+                for limit in [lowest, (lowest + highest) / 2, highest] {
+                    for stop_at in lowest - 1..=limit + 1 {
+                        let exists = costs.iter().any(|&cost| (stop_at..=limit).contains(&cost));
+                        let result = capped_max_path(&grid, start, end, movement, UNLIMITED, Some(limit), Some(stop_at)).unwrap();
+                        assert_eq!(result.cost >= stop_at, exists, "{:?} seed {} range {}..={}", movement, seed, stop_at, limit);
+                        assert!(result.cost <= limit);
+                        // Sans chemin dans l'intervalle, la recherche est allée au bout
+                        assert_eq!(result.optimal, !exists);
+                        assert_eq!(checked_cost(&grid, &result.path, start, end, movement), result.cost);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn walled_end_has_no_path() {
+        let grid = Grid::from_rows(vec![vec![Some(1), None], vec![None, Some(2)]]);
+        assert_eq!(max_path_dfs(&grid, (0, 0), (1, 1), MOVEMENTS[0], UNLIMITED), None);
+    }
+}
@@ -0,0 +1,319 @@
+mod astar;
+mod bellman_ford;
+mod constrained;
+mod dial;
+mod dijkstra;
+mod max_path;
+mod yen;
+
+pub use astar::{astar, Heuristic};
+pub use bellman_ford::{bellman_ford, NegativeCycle};
+pub use constrained::step_limited_path;
+pub use dial::{dial_dijkstra, dial_max_step, MAX_DIAL_STEP};
+pub use dijkstra::dijkstra;
+pub use max_path::{capped_max_path, greedy_max_path, max_path_dfs, SearchBudget};
+pub use yen::{k_shortest_paths, path_overlap};
+
+use crate::grid::Grid;
+use crate::topology::Movement;
+use crate::Coord;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub(crate) struct State {
+    pub(crate) cost: i64,
+    pub(crate) position: Coord,
+}
+
+impl Ord for State {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.cost.cmp(&self.cost)
+            .then_with(|| self.position.cmp(&other.position))
+    }
+}
+
+impl PartialOrd for State {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// Suivi d'une recherche : compteurs, animation...
+pub trait SearchObserver {
+    fn started(&mut self, _start: Coord) {}
+    fn discovered(&mut self, _pos: Coord) {}
+    fn expanded(&mut self, _pos: Coord) {}
+}
+
+impl SearchObserver for () {}
+
+#[derive(Copy, Clone, Debug, Default)]
+pub struct SearchStats {
+    pub nodes_expanded: u64,
+}
+
+impl SearchObserver for SearchStats {
+    fn expanded(&mut self, _pos: Coord) {
+        self.nodes_expanded += 1;
+    }
+}
+
+// Compte les nœuds développés tout en relayant les événements
+struct Counting<'a> {
+    inner: &'a mut dyn SearchObserver,
+    stats: SearchStats,
+}
+
+impl SearchObserver for Counting<'_> {
+    fn started(&mut self, start: Coord) {
+        self.inner.started(start);
+    }
+
+    fn discovered(&mut self, pos: Coord) {
+        self.inner.discovered(pos);
+    }
+
+    fn expanded(&mut self, pos: Coord) {
+        self.stats.expanded(pos);
+        self.inner.expanded(pos);
+    }
+}
+
+/// Résultat typé d'un solveur
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PathResult {
+    pub cost: i64,
+    pub path: Vec<Coord>,
+    // false si le solveur ne garantit pas l'optimalité (heuristique, budget épuisé)
+    pub optimal: bool,
+    pub nodes_expanded: u64,
+}
+
+/// Interface commune à tous les algorithmes de recherche de chemin
+pub trait Solver {
+    fn name(&self) -> &'static str;
+
+    fn solve(&self, grid: &Grid, start: Coord, end: Coord, observer: &mut dyn SearchObserver) -> Option<PathResult>;
+
+    /// Comme `solve`, mais un cycle négatif atteignable est une erreur plutôt qu'une absence de chemin
+    fn try_solve(&self, grid: &Grid, start: Coord, end: Coord, observer: &mut dyn SearchObserver) -> Result<Option<PathResult>, NegativeCycle> {
+        Ok(self.solve(grid, start, end, observer))
+    }
+}
+
+/// À partir de ce nombre de cellules, `Dijkstra` passe à la file à seaux quand les coûts le permettent
+pub const DIAL_MIN_CELLS: usize = 1 << 16;
+
+#[derive(Copy, Clone, Debug)]
+pub struct Dijkstra {
+    pub movement: Movement,
+}
+
+impl Solver for Dijkstra {
+    fn name(&self) -> &'static str {
+        "dijkstra"
+    }
+
+    fn solve(&self, grid: &Grid, start: Coord, end: Coord, observer: &mut dyn SearchObserver) -> Option<PathResult> {
+        let mut counting = Counting { inner: observer, stats: SearchStats::default() };
+        let (cost, path) = if grid.len() >= DIAL_MIN_CELLS && dial_max_step(grid, self.movement).is_some() {
+            dial_dijkstra(grid, start, end, self.movement, &mut counting)?
+        } else {
+            dijkstra(grid, start, end, self.movement, &mut counting)?
+        };
+        Some(PathResult { cost, path, optimal: true, nodes_expanded: counting.stats.nodes_expanded })
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct AStar {
+    pub movement: Movement,
+    pub heuristic: Heuristic,
+}
+
+impl Solver for AStar {
+    fn name(&self) -> &'static str {
+        "astar"
+    }
+
+    fn solve(&self, grid: &Grid, start: Coord, end: Coord, observer: &mut dyn SearchObserver) -> Option<PathResult> {
+        let mut counting = Counting { inner: observer, stats: SearchStats::default() };
+        let (cost, path) = astar(grid, start, end, self.heuristic, self.movement, &mut counting)?;
+        Some(PathResult { cost, path, optimal: true, nodes_expanded: counting.stats.nodes_expanded })
+    }
+}
+
+// Accepte les coûts négatifs ; un cycle négatif rend le chemin introuvable
+#[derive(Copy, Clone, Debug)]
+pub struct BellmanFord {
+    pub movement: Movement,
+}
+
+impl Solver for BellmanFord {
+    fn name(&self) -> &'static str {
+        "bellman-ford"
+    }
+
+    fn solve(&self, grid: &Grid, start: Coord, end: Coord, observer: &mut dyn SearchObserver) -> Option<PathResult> {
+        self.try_solve(grid, start, end, observer).ok().flatten()
+    }
+
+    fn try_solve(&self, grid: &Grid, start: Coord, end: Coord, observer: &mut dyn SearchObserver) -> Result<Option<PathResult>, NegativeCycle> {
+        let mut counting = Counting { inner: observer, stats: SearchStats::default() };
+        let found = bellman_ford(grid, start, end, self.movement, &mut counting)?;
+        Ok(found.map(|(cost, path)| PathResult { cost, path, optimal: true, nodes_expanded: counting.stats.nodes_expanded }))
+    }
+}
+
+// Chemin minimal d'au plus `max_steps` pas, coûts positifs uniquement
+#[derive(Copy, Clone, Debug)]
+pub struct StepLimited {
+    pub movement: Movement,
+    pub max_steps: usize,
+}
+
+impl Solver for StepLimited {
+    fn name(&self) -> &'static str {
+        "step-limited"
+    }
+
+    fn solve(&self, grid: &Grid, start: Coord, end: Coord, observer: &mut dyn SearchObserver) -> Option<PathResult> {
+        let mut counting = Counting { inner: observer, stats: SearchStats::default() };
+        let (cost, path) = step_limited_path(grid, start, end, self.movement, self.max_steps, &mut counting)?;
+        Some(PathResult { cost, path, optimal: true, nodes_expanded: counting.stats.nodes_expanded })
+    }
+}
+
+/// Algorithme du chemin minimal, au choix de l'utilisateur
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Algorithm {
+    Dijkstra,
+    Astar,
+    /// Slower, but accepts negative costs and detects negative cycles
+    BellmanFord,
+}
+
+impl Algorithm {
+    pub fn solver(self, movement: Movement, heuristic: Heuristic) -> Box<dyn Solver> {
+        match self {
+            Algorithm::Dijkstra => Box::new(Dijkstra { movement }),
+            Algorithm::Astar => Box::new(AStar { movement, heuristic }),
+            Algorithm::BellmanFord => Box::new(BellmanFord { movement }),
+        }
+    }
+
+    /// Solveur du chemin minimal sur `grid` : les coûts négatifs imposent Bellman-Ford et excluent A*.
+    /// Les cycles négatifs sont signalés par `Solver::try_solve`.
+    pub fn for_grid(self, grid: &Grid, movement: Movement, heuristic: Heuristic) -> Result<Box<dyn Solver>, String> {
+        match (grid.has_negative(), self) {
+            (true, Algorithm::Astar) => Err("--algorithm astar needs non-negative costs".to_string()),
+            (true, _) => Ok(Algorithm::BellmanFord.solver(movement, heuristic)),
+            (false, algorithm) => Ok(algorithm.solver(movement, heuristic)),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct MaxPathDfs {
+    pub movement: Movement,
+    pub budget: SearchBudget,
+}
+
+impl Solver for MaxPathDfs {
+    fn name(&self) -> &'static str {
+        "branch-and-bound"
+    }
+
+    fn solve(&self, grid: &Grid, start: Coord, end: Coord, _observer: &mut dyn SearchObserver) -> Option<PathResult> {
+        max_path_dfs(grid, start, end, self.movement, self.budget)
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct GreedyMaxPath {
+    pub movement: Movement,
+}
+
+impl Solver for GreedyMaxPath {
+    fn name(&self) -> &'static str {
+        "greedy"
+    }
+
+    fn solve(&self, grid: &Grid, start: Coord, end: Coord, _observer: &mut dyn SearchObserver) -> Option<PathResult> {
+        let (cost, path) = greedy_max_path(grid, start, end, self.movement)?;
+        let nodes_expanded = path.len() as u64;
+        Some(PathResult { cost, path, optimal: false, nodes_expanded })
+    }
+}
+
+// Outils partagés par les tests des solveurs : petites grilles et énumération exhaustive des chemins
+#[cfg(test)]
+pub(crate) mod testing {
+    use crate::grid::{CellWidth, Grid};
+    use crate::topology::{Movement, Topology};
+    use crate::Coord;
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha8Rng;
+
+    pub(crate) const MOVEMENTS: [Movement; 4] = [
+        Movement { topology: Topology::Four, diagonal_cost: 1.0 },
+        Movement { topology: Topology::Eight, diagonal_cost: 1.0 },
+        Movement { topology: Topology::Eight, diagonal_cost: 1.5 },
+        Movement { topology: Topology::Hex, diagonal_cost: 1.0 },
+    ];
+
+    /// Grille aléatoire de valeurs 0..=9 avec environ `walls` de murs ; `negative` ajoute des valeurs jusqu'à -3.
+    /// Les coins restent ouverts.
+    pub(crate) fn tiny_grid(rows: usize, cols: usize, walls: f64, negative: bool, seed: u64) -> Grid {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let low = if negative { -3 } else { 0 };
+        let cells = (0..rows * cols)
+            .map(|i| {
+                let corner = i == 0 || i == rows * cols - 1;
+                let value = rng.gen_range(low..=9);
+                (corner || !rng.gen_bool(walls)).then_some(value)
+            })
+            .collect();
+        Grid::new(rows, cols, cells).with_cell_type(CellWidth::U8, negative)
+    }
+
+    /// Tous les chemins simples de `start` à `end` avec leur coût, dans l'ordre de la recherche en profondeur
+    pub(crate) fn simple_paths(grid: &Grid, start: Coord, end: Coord, movement: Movement) -> Vec<(i64, Vec<Coord>)> {
+        fn walk(grid: &Grid, end: Coord, movement: Movement, path: &mut Vec<Coord>, cost: i64, found: &mut Vec<(i64, Vec<Coord>)>) {
+            let current = *path.last().unwrap();
+            if current == end {
+                found.push((cost, path.clone()));
+                return;
+            }
+            for next in movement.neighbors(current, grid).collect::<Vec<_>>() {
+                if !path.contains(&next) {
+                    path.push(next);
+                    walk(grid, end, movement, path, cost + movement.step_cost(grid, current, next), found);
+                    path.pop();
+                }
+            }
+        }
+        let mut found = Vec::new();
+        if !grid.is_wall(start) && !grid.is_wall(end) {
+            walk(grid, end, movement, &mut vec![start], 0, &mut found);
+        }
+        found
+    }
+
+    /// Coût d'un chemin, après avoir vérifié qu'il relie `start` à `end` par des voisins sans repasser par une cellule
+    pub(crate) fn checked_cost(grid: &Grid, path: &[Coord], start: Coord, end: Coord, movement: Movement) -> i64 {
+        assert_eq!((path.first(), path.last()), (Some(&start), Some(&end)), "{:?}", path);
+        for (i, pos) in path.iter().enumerate() {
+            assert!(!grid.is_wall(*pos), "{:?} crosses the wall {:?}", path, pos);
+            assert!(!path[..i].contains(pos), "{:?} visits {:?} twice", path, pos);
+        }
+        path.windows(2)
+            .map(|pair| {
+                assert_eq!(movement.distance(pair[0], pair[1]), 1, "{:?} jumps from {:?} to {:?}", path, pair[0], pair[1]);
+                movement.step_cost(grid, pair[0], pair[1])
+            })
+            .sum()
+    }
+}
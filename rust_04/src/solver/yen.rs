@@ -0,0 +1,165 @@
+use super::{SearchObserver, State};
+use crate::grid::Grid;
+use crate::topology::Movement;
+use crate::Coord;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+// Dijkstra qui évite des nœuds et des arêtes, pour les chemins de déviation de Yen
+fn restricted_dijkstra(
+    grid: &Grid,
+    start: Coord,
+    end: Coord,
+    movement: Movement,
+    blocked: &HashSet<Coord>,
+    banned: &HashSet<(Coord, Coord)>,
+    observer: &mut dyn SearchObserver,
+) -> Option<(i64, Vec<Coord>)> {
+    let mut dist: HashMap<Coord, i64> = HashMap::new();
+    let mut predecessors: HashMap<Coord, Coord> = HashMap::new();
+    let mut heap = BinaryHeap::new();
+
+    dist.insert(start, 0);
+    heap.push(State { cost: 0, position: start });
+    observer.started(start);
+
+    while let Some(State { cost, position }) = heap.pop() {
+        if position == end {
+            let mut path = vec![end];
+            let mut curr = end;
+            while curr != start {
+                curr = predecessors[&curr];
+                path.push(curr);
+            }
+            path.reverse();
+            return Some((cost, path));
+        }
+        if cost > dist[&position] {
+            continue;
+        }
+        observer.expanded(position);
+
+        for next in movement.neighbors(position, grid) {
+            if blocked.contains(&next) || banned.contains(&(position, next)) {
+                continue;
+            }
+            let Some(total) = cost.checked_add(movement.step_cost(grid, position, next)) else { continue };
+            if total < dist.get(&next).copied().unwrap_or(i64::MAX) {
+                dist.insert(next, total);
+                predecessors.insert(next, position);
+                heap.push(State { cost: total, position: next });
+                observer.discovered(next);
+            }
+        }
+    }
+    None
+}
+
+/// Les `k` chemins sans boucle les moins coûteux (algorithme de Yen), du meilleur au pire.
+pub fn k_shortest_paths(
+    grid: &Grid,
+    start: Coord,
+    end: Coord,
+    movement: Movement,
+    k: usize,
+    observer: &mut dyn SearchObserver,
+) -> Vec<(i64, Vec<Coord>)> {
+    let no_nodes = HashSet::new();
+    let no_edges = HashSet::new();
+    let Some(best) = restricted_dijkstra(grid, start, end, movement, &no_nodes, &no_edges, observer) else {
+        return Vec::new();
+    };
+
+    let mut found = vec![best];
+    let mut candidates = BinaryHeap::new();
+    let mut seen: HashSet<Vec<Coord>> = found.iter().map(|(_, path)| path.clone()).collect();
+
+    while found.len() < k {
+        let (_, previous) = &found[found.len() - 1];
+        let mut root_cost = 0i64;
+        for i in 0..previous.len() - 1 {
+            let spur = previous[i];
+            let root = &previous[..=i];
+            if i > 0 {
+                root_cost = root_cost.saturating_add(movement.step_cost(grid, previous[i - 1], spur));
+            }
+
+            // Les chemins déjà retenus qui partagent cette racine ne peuvent pas être repris
+            let banned: HashSet<(Coord, Coord)> = found.iter()
+                .filter(|(_, path)| path.len() > i + 1 && &path[..=i] == root)
+                .map(|(_, path)| (path[i], path[i + 1]))
+                .collect();
+            let blocked: HashSet<Coord> = root[..i].iter().copied().collect();
+
+            if let Some((spur_cost, spur_path)) = restricted_dijkstra(grid, spur, end, movement, &blocked, &banned, observer) {
+                let mut path = root[..i].to_vec();
+                path.extend(spur_path);
+                if seen.insert(path.clone()) {
+                    candidates.push(Reverse((root_cost.saturating_add(spur_cost), path)));
+                }
+            }
+        }
+
+        match candidates.pop() {
+            Some(Reverse(candidate)) => found.push(candidate),
+            None => break,
+        }
+    }
+    found
+}
+
+/// Part des cellules de `path` également présentes dans `reference`, en pourcentage
+pub fn path_overlap(path: &[Coord], reference: &[Coord]) -> f64 {
+    if path.is_empty() {
+        return 0.0;
+    }
+    let reference: HashSet<&Coord> = reference.iter().collect();
+    let shared = path.iter().filter(|pos| reference.contains(pos)).count();
+    shared as f64 * 100.0 / path.len() as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::testing::{checked_cost, simple_paths, tiny_grid, MOVEMENTS};
+
+    #[test]
+    fn matches_exhaustive_enumeration() {
+        for movement in MOVEMENTS {
+            for (rows, cols) in [(2, 3), (3, 3), (3, 4)] {
+                for seed in 0..8 {
+                    let grid = tiny_grid(rows, cols, if seed % 2 == 0 { 0.0 } else { 0.25 }, false, seed);
+                    let (start, end) = ((0, 0), (rows - 1, cols - 1));
+                    let mut expected: Vec<i64> = simple_paths(&grid, start, end, movement).into_iter().map(|(cost, _)| cost).collect();
+                    expected.sort_unstable();
+                    // Au-delà du nombre de chemins simples, tous sont rendus
+                    let beyond = (expected.len() <= 20).then_some(expected.len() + 5);
+                    for k in [1, 3, 12].into_iter().chain(beyond) {
+                        let found = k_shortest_paths(&grid, start, end, movement, k, &mut ());
+                        let costs: Vec<i64> = found.iter().map(|(cost, _)| *cost).collect();
+                        assert_eq!(costs, expected[..k.min(expected.len())], "{:?} {}x{} seed {} k {}", movement, rows, cols, seed, k);
+                        let distinct: HashSet<&Vec<Coord>> = found.iter().map(|(_, path)| path).collect();
+                        assert_eq!(distinct.len(), found.len());
+                        for (cost, path) in &found {
+                            assert_eq!(checked_cost(&grid, path, start, end, movement), *cost);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn unreachable_end_has_no_path() {
+        let grid = Grid::from_rows(vec![vec![Some(1), None, Some(1)]]);
+        assert!(k_shortest_paths(&grid, (0, 0), (0, 2), MOVEMENTS[0], 3, &mut ()).is_empty());
+    }
+
+    #[test]
+    fn overlap_counts_cells_of_the_first_path() {
+        let path = [(0, 0), (0, 1), (1, 1), (2, 1)];
+        assert_eq!(path_overlap(&path, &[(0, 0), (1, 0), (2, 0), (2, 1)]), 50.0);
+        assert_eq!(path_overlap(&path, &path), 100.0);
+        assert_eq!(path_overlap(&[], &path), 0.0);
+    }
+}
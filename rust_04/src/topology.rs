@@ -0,0 +1,223 @@
+use crate::grid::Grid;
+use crate::Coord;
+use clap::ValueEnum;
+use serde::Serialize;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum, Serialize)]
+pub enum Topology {
+    /// Orthogonal neighbours only
+    #[value(name = "4")]
+    #[serde(rename = "4")]
+    Four,
+    /// Orthogonal and diagonal neighbours
+    #[value(name = "8")]
+    #[serde(rename = "8")]
+    Eight,
+    /// Hexagonal tiles, odd rows shifted right by half a cell
+    #[value(name = "hex")]
+    #[serde(rename = "hex")]
+    Hex,
+}
+
+const ORTHOGONAL_MOVES: [(isize, isize); 4] = [(0, 1), (0, -1), (1, 0), (-1, 0)];
+const ALL_MOVES: [(isize, isize); 8] = [(0, 1), (0, -1), (1, 0), (-1, 0), (1, 1), (1, -1), (-1, 1), (-1, -1)];
+const HEX_EVEN_ROW_MOVES: [(isize, isize); 6] = [(0, 1), (0, -1), (-1, -1), (-1, 0), (1, -1), (1, 0)];
+const HEX_ODD_ROW_MOVES: [(isize, isize); 6] = [(0, 1), (0, -1), (-1, 0), (-1, 1), (1, 0), (1, 1)];
+
+// Modèle de déplacement partagé par tous les solveurs et l'affichage
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Movement {
+    pub topology: Topology,
+    pub diagonal_cost: f64,
+}
+
+impl Movement {
+    // Voisins traversables : les murs ne sont jamais renvoyés
+    pub fn neighbors<'a>(&self, pos: Coord, grid: &'a Grid) -> impl Iterator<Item = Coord> + 'a {
+        let rows = grid.rows();
+        let cols = grid.cols();
+        let moves: &'static [(isize, isize)] = match self.topology {
+            Topology::Four => &ORTHOGONAL_MOVES,
+            Topology::Eight => &ALL_MOVES,
+            Topology::Hex if pos.0.is_multiple_of(2) => &HEX_EVEN_ROW_MOVES,
+            Topology::Hex => &HEX_ODD_ROW_MOVES,
+        };
+        moves.iter().filter_map(move |(dr, dc)| {
+            let nr = pos.0 as isize + dr;
+            let nc = pos.1 as isize + dc;
+            if nr >= 0 && nr < rows as isize && nc >= 0 && nc < cols as isize {
+                let next = (nr as usize, nc as usize);
+                grid.cell(next).map(|_| next)
+            } else {
+                None
+            }
+        })
+    }
+
+    pub fn is_diagonal(&self, from: Coord, to: Coord) -> bool {
+        self.topology == Topology::Eight && from.0 != to.0 && from.1 != to.1
+    }
+
+    // Coût pour entrer dans `to` depuis `from`
+    pub fn step_cost(&self, grid: &Grid, from: Coord, to: Coord) -> i64 {
+        let value = grid.cell(to).unwrap_or(0);
+        if self.is_diagonal(from, to) {
+            (value as f64 * self.diagonal_cost).round() as i64
+        } else {
+            value
+        }
+    }
+
+    // Borne inférieure du coût d'un pas quelconque entrant dans une cellule de valeur `value`
+    pub fn min_step_cost(&self, value: i64) -> i64 {
+        if self.topology == Topology::Eight && self.diagonal_cost < 1.0 {
+            (value as f64 * self.diagonal_cost).floor() as i64
+        } else {
+            value
+        }
+    }
+
+    // Borne supérieure, utilisée par la recherche du chemin maximal
+    pub fn max_step_cost(&self, value: i64) -> i64 {
+        if self.topology == Topology::Eight && self.diagonal_cost > 1.0 {
+            (value as f64 * self.diagonal_cost).round() as i64
+        } else {
+            value
+        }
+    }
+
+    // Nombre minimal de pas entre deux cellules
+    pub fn distance(&self, from: Coord, to: Coord) -> u32 {
+        let dr = from.0.abs_diff(to.0);
+        let dc = from.1.abs_diff(to.1);
+        match self.topology {
+            Topology::Four => (dr + dc) as u32,
+            Topology::Eight => dr.max(dc) as u32,
+            Topology::Hex => {
+                // Conversion des coordonnées décalées en coordonnées cubiques
+                let cube = |(r, c): Coord| {
+                    let x = c as isize - (r as isize - (r as isize & 1)) / 2;
+                    let z = r as isize;
+                    (x, z, -x - z)
+                };
+                let (x1, z1, y1) = cube(from);
+                let (x2, z2, y2) = cube(to);
+                x1.abs_diff(x2).max(y1.abs_diff(y2)).max(z1.abs_diff(z2)) as u32
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn open_grid(rows: usize, cols: usize) -> Grid {
+        Grid::new(rows, cols, (0..rows * cols).map(|i| Some(i as i64)).collect())
+    }
+
+    fn sorted_neighbors(movement: Movement, pos: Coord, grid: &Grid) -> Vec<Coord> {
+        let mut neighbors: Vec<Coord> = movement.neighbors(pos, grid).collect();
+        neighbors.sort();
+        neighbors
+    }
+
+    const HEX: Movement = Movement { topology: Topology::Hex, diagonal_cost: 1.0 };
+
+    #[test]
+    fn hex_neighbours_follow_the_odd_row_shift() {
+        let grid = open_grid(5, 5);
+        // Ligne paire : les voisins du dessus et du dessous sont à gauche ; ligne impaire : à droite
+        assert_eq!(sorted_neighbors(HEX, (2, 2), &grid), [(1, 1), (1, 2), (2, 1), (2, 3), (3, 1), (3, 2)]);
+        assert_eq!(sorted_neighbors(HEX, (1, 2), &grid), [(0, 2), (0, 3), (1, 1), (1, 3), (2, 2), (2, 3)]);
+        // Bords de la grille
+        assert_eq!(sorted_neighbors(HEX, (0, 0), &grid), [(0, 1), (1, 0)]);
+        assert_eq!(sorted_neighbors(HEX, (1, 4), &grid), [(0, 4), (1, 3), (2, 4)]);
+        assert_eq!(sorted_neighbors(HEX, (4, 0), &grid), [(3, 0), (4, 1)]);
+        for i in 0..grid.len() {
+            let pos = grid.coord(i);
+            for next in HEX.neighbors(pos, &grid) {
+                assert_eq!(HEX.distance(pos, next), 1);
+                assert!(HEX.neighbors(next, &grid).any(|back| back == pos), "{:?} -> {:?} is one-way", pos, next);
+            }
+        }
+    }
+
+    #[test]
+    fn hex_distance_uses_cube_coordinates() {
+        let cases = [
+            ((2, 2), (0, 2), 2),
+            ((2, 2), (0, 1), 2),
+            ((2, 2), (0, 3), 2),
+            ((2, 2), (0, 4), 3),
+            ((1, 2), (3, 3), 2),
+            ((1, 2), (3, 1), 2),
+            ((1, 2), (3, 0), 3),
+            ((0, 0), (4, 4), 6),
+            ((3, 0), (3, 4), 4),
+        ];
+        for (from, to, distance) in cases {
+            assert_eq!(HEX.distance(from, to), distance, "{:?} -> {:?}", from, to);
+            assert_eq!(HEX.distance(to, from), distance);
+        }
+        // La distance est le nombre de pas d'un parcours en largeur
+        let grid = open_grid(6, 7);
+        for from in [(2, 3), (3, 3)] {
+            let mut steps = vec![u32::MAX; grid.len()];
+            let mut frontier = vec![from];
+            steps[grid.index(from)] = 0;
+            for step in 1.. {
+                let next: Vec<Coord> = frontier.iter()
+                    .flat_map(|&pos| HEX.neighbors(pos, &grid).collect::<Vec<_>>())
+                    .filter(|&pos| steps[grid.index(pos)] == u32::MAX)
+                    .collect();
+                if next.is_empty() {
+                    break;
+                }
+                for &pos in &next {
+                    steps[grid.index(pos)] = step;
+                }
+                frontier = next;
+            }
+            for (i, &step) in steps.iter().enumerate() {
+                assert_eq!(HEX.distance(from, grid.coord(i)), step, "{:?} -> {:?}", from, grid.coord(i));
+            }
+        }
+    }
+
+    #[test]
+    fn diagonal_cost_applies_to_eight_way_diagonals_only() {
+        let grid = Grid::new(2, 2, vec![Some(10), Some(7), Some(3), Some(5)]);
+        let eight = Movement { topology: Topology::Eight, diagonal_cost: 1.5 };
+        assert_eq!(sorted_neighbors(eight, (0, 0), &grid), [(0, 1), (1, 0), (1, 1)]);
+        assert_eq!(eight.step_cost(&grid, (0, 0), (1, 1)), 8);
+        assert_eq!(eight.step_cost(&grid, (1, 1), (0, 0)), 15);
+        assert_eq!(eight.step_cost(&grid, (0, 1), (1, 0)), 5);
+        assert_eq!(eight.step_cost(&grid, (0, 0), (0, 1)), 7);
+        assert_eq!((eight.min_step_cost(5), eight.max_step_cost(5)), (5, 8));
+
+        let cheap = Movement { topology: Topology::Eight, diagonal_cost: 0.5 };
+        assert_eq!(cheap.step_cost(&grid, (0, 1), (1, 0)), 2);
+        assert_eq!((cheap.min_step_cost(5), cheap.max_step_cost(5)), (2, 5));
+        assert_eq!(eight.distance((0, 0), (3, 5)), 5);
+
+        // Les autres topologies ignorent le multiplicateur
+        for topology in [Topology::Four, Topology::Hex] {
+            let movement = Movement { topology, diagonal_cost: 1.5 };
+            assert!(!movement.is_diagonal((0, 0), (1, 1)));
+            assert_eq!(movement.step_cost(&grid, (0, 0), (1, 1)), 5);
+        }
+        let four = Movement { topology: Topology::Four, diagonal_cost: 1.0 };
+        assert_eq!(sorted_neighbors(four, (0, 0), &grid), [(0, 1), (1, 0)]);
+        assert_eq!(four.distance((0, 0), (3, 5)), 8);
+    }
+
+    #[test]
+    fn walls_are_never_neighbours() {
+        let grid = Grid::new(2, 2, vec![Some(1), None, None, Some(1)]);
+        for topology in [Topology::Four, Topology::Hex] {
+            assert_eq!(sorted_neighbors(Movement { topology, diagonal_cost: 1.0 }, (0, 0), &grid), []);
+        }
+        assert_eq!(sorted_neighbors(Movement { topology: Topology::Eight, diagonal_cost: 1.0 }, (0, 0), &grid), [(1, 1)]);
+    }
+}
@@ -0,0 +1,220 @@
+use crate::grid::Grid;
+use crate::solver::{Algorithm, Heuristic};
+use crate::topology::Movement;
+use crate::Coord;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Problème relevé sur un chemin fourni, `step` est l'indice de la cellule fautive
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct PathIssue {
+    pub step: usize,
+    pub message: String,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Verification {
+    pub valid: bool,
+    pub length: usize,
+    // Coût du chemin, calculable dès que toutes ses cellules sont dans la grille et hors des murs
+    pub cost: Option<i64>,
+    pub optimum: Option<i64>,
+    pub gap: Option<i64>,
+    pub gap_percent: Option<f64>,
+    pub issues: Vec<PathIssue>,
+}
+
+/// Lit un chemin `(r,c)->(r,c)->...`, éventuellement sur plusieurs lignes. Une sortie complète de
+/// `print_path_details` est acceptée : seules les lignes qui suivent `Path:` sont alors lues.
+pub fn parse_path(text: &str) -> Result<Vec<Coord>, String> {
+    let mut lines: Vec<&str> = text.lines().collect();
+    if let Some(header) = lines.iter().position(|line| line.trim() == "Path:") {
+        lines = lines[header + 1..].iter().copied().take_while(|line| !line.trim().is_empty()).collect();
+    }
+    let joined: String = lines.concat().chars().filter(|ch| !ch.is_whitespace()).collect();
+    if joined.is_empty() {
+        return Err("the path is empty".to_string());
+    }
+    joined.split("->")
+        .map(|cell| {
+            let inner = cell.strip_prefix('(').and_then(|c| c.strip_suffix(')'))
+                .ok_or_else(|| format!("expected (r,c) but got '{}'", cell))?;
+            let (r, c) = inner.split_once(',').ok_or_else(|| format!("expected (r,c) but got '{}'", cell))?;
+            let r = r.parse::<usize>().map_err(|e| format!("invalid row in '{}': {}", cell, e))?;
+            let c = c.parse::<usize>().map_err(|e| format!("invalid column in '{}': {}", cell, e))?;
+            Ok((r, c))
+        })
+        .collect()
+}
+
+/// Vérifie un chemin fourni (cellules dans la grille, hors des murs, voisines, sans retour, bonnes extrémités)
+/// et le compare au chemin minimal entre `start` et `end`, trouvé par `algorithm` adapté à la grille.
+pub fn verify_path(grid: &Grid, path: &[Coord], start: Coord, end: Coord, algorithm: Algorithm, movement: Movement, heuristic: Heuristic) -> Result<Verification, String> {
+    let mut issues = Vec::new();
+    let mut issue = |step: usize, message: String| issues.push(PathIssue { step, message });
+
+    match (path.first(), path.last()) {
+        (Some(&first), Some(&last)) => {
+            if first != start {
+                issue(0, format!("starts at ({},{}) instead of ({},{})", first.0, first.1, start.0, start.1));
+            }
+            if last != end {
+                issue(path.len() - 1, format!("ends at ({},{}) instead of ({},{})", last.0, last.1, end.0, end.1));
+            }
+        }
+        _ => issue(0, "the path is empty".to_string()),
+    }
+
+    let mut first_visit: HashMap<Coord, usize> = HashMap::new();
+    for (step, &(r, c)) in path.iter().enumerate() {
+        if !grid.contains((r, c)) {
+            issue(step, format!("({},{}) is outside the {}x{} grid", r, c, grid.rows(), grid.cols()));
+        } else if grid.is_wall((r, c)) {
+            issue(step, format!("({},{}) is a wall", r, c));
+        }
+        if let Some(previous) = first_visit.insert((r, c), step) {
+            issue(step, format!("({},{}) was already visited at step {}", r, c, previous));
+        }
+        if step > 0 {
+            let (pr, pc) = path[step - 1];
+            if movement.distance((pr, pc), (r, c)) != 1 {
+                issue(step, format!("({},{}) and ({},{}) are not adjacent", pr, pc, r, c));
+            }
+        }
+    }
+    issues.sort_by_key(|issue| issue.step);
+
+    let in_grid = !path.is_empty() && path.iter().all(|&pos| grid.contains(pos) && !grid.is_wall(pos));
+    let cost = in_grid.then(|| {
+        path.windows(2).fold(0i64, |total, pair| total.saturating_add(movement.step_cost(grid, pair[0], pair[1])))
+    });
+
+    let reachable = grid.contains(start) && grid.contains(end) && !grid.is_wall(start) && !grid.is_wall(end);
+    let solver = algorithm.for_grid(grid, movement, heuristic)?;
+    let optimum = if reachable {
+        solver.try_solve(grid, start, end, &mut ()).map_err(|cycle| format!("No minimum cost path: {}", cycle))?.map(|result| result.cost)
+    } else {
+        None
+    };
+
+    let valid = issues.is_empty();
+    let gap = match (valid, cost, optimum) {
+        (true, Some(cost), Some(optimum)) => Some(cost - optimum),
+        _ => None,
+    };
+    Ok(Verification {
+        valid,
+        length: path.len(),
+        cost,
+        optimum,
+        gap,
+        gap_percent: gap.zip(optimum).filter(|&(_, optimum)| optimum != 0).map(|(gap, optimum)| gap as f64 * 100.0 / optimum.abs() as f64),
+        issues,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::path_details;
+    use crate::solver::testing::{tiny_grid, MOVEMENTS};
+    use crate::solver::Dijkstra;
+    use crate::solver::Solver;
+
+    const FOUR: Movement = MOVEMENTS[0];
+
+    fn verify(grid: &Grid, path: &[Coord]) -> Verification {
+        verify_path(grid, path, (0, 0), (2, 3), Algorithm::Dijkstra, FOUR, Heuristic::Manhattan).unwrap()
+    }
+
+    fn messages(verification: &Verification) -> Vec<(usize, &str)> {
+        verification.issues.iter().map(|issue| (issue.step, issue.message.as_str())).collect()
+    }
+
+    fn grid() -> Grid {
+        // 1 1 1 1
+        // 5 X 5 1
+        // 5 5 5 1
+        Grid::new(3, 4, [1, 1, 1, 1, 5, -1, 5, 1, 5, 5, 5, 1].iter().map(|&v| (v >= 0).then_some(v)).collect())
+    }
+
+    #[test]
+    fn optimal_and_suboptimal_routes_are_scored() {
+        let grid = grid();
+        let best = verify(&grid, &[(0, 0), (0, 1), (0, 2), (0, 3), (1, 3), (2, 3)]);
+        assert!(best.valid);
+        assert_eq!((best.cost, best.optimum, best.gap), (Some(5), Some(5), Some(0)));
+
+        let detour = verify(&grid, &[(0, 0), (1, 0), (2, 0), (2, 1), (2, 2), (2, 3)]);
+        assert!(detour.valid && detour.issues.is_empty());
+        assert_eq!((detour.cost, detour.gap, detour.gap_percent), (Some(21), Some(16), Some(320.0)));
+    }
+
+    #[test]
+    fn broken_routes_list_every_issue_by_step() {
+        let grid = grid();
+        let jump = verify(&grid, &[(0, 0), (0, 1), (0, 3), (1, 3), (2, 3)]);
+        assert!(!jump.valid);
+        assert_eq!(messages(&jump), [(2, "(0,1) and (0,3) are not adjacent")]);
+        // Le coût reste calculé pour un chemin non valide mais entièrement dans la grille
+        assert_eq!((jump.cost, jump.gap), (Some(4), None));
+
+        let wall = verify(&grid, &[(0, 0), (0, 1), (1, 1), (2, 1), (2, 2), (2, 3)]);
+        assert_eq!(messages(&wall), [(2, "(1,1) is a wall")]);
+        assert_eq!(wall.cost, None);
+
+        let ends = verify(&grid, &[(0, 1), (0, 2), (0, 3), (1, 3)]);
+        assert_eq!(messages(&ends), [(0, "starts at (0,1) instead of (0,0)"), (3, "ends at (1,3) instead of (2,3)")]);
+
+        let outside = verify(&grid, &[(0, 0), (0, 1), (0, 2), (0, 3), (0, 4), (1, 3), (2, 3)]);
+        assert_eq!(messages(&outside), [(4, "(0,4) is outside the 3x4 grid"), (5, "(0,4) and (1,3) are not adjacent")]);
+
+        let revisit = verify(&grid, &[(0, 0), (0, 1), (0, 0), (0, 1), (0, 2), (0, 3), (1, 3), (2, 3)]);
+        assert_eq!(messages(&revisit), [(2, "(0,0) was already visited at step 0"), (3, "(0,1) was already visited at step 1")]);
+
+        assert_eq!(messages(&verify(&grid, &[])), [(0, "the path is empty")]);
+    }
+
+    #[test]
+    fn wrong_cost_is_reported_as_a_gap_to_the_optimum() {
+        for movement in MOVEMENTS {
+            for seed in 0..10 {
+                let grid = tiny_grid(4, 5, 0.2, false, seed);
+                let Some(best) = Dijkstra { movement }.solve(&grid, (0, 0), (3, 4), &mut ()) else { continue };
+                let verification = verify_path(&grid, &best.path, (0, 0), (3, 4), Algorithm::Astar, movement, Heuristic::Manhattan).unwrap();
+                assert!(verification.valid, "{:?}", verification.issues);
+                assert_eq!((verification.cost, verification.gap), (Some(best.cost), Some(0)));
+            }
+        }
+        // Sur une carte plus chère, le même chemin coûte plus que l'optimum annoncé
+        let mut grid = grid();
+        let path = [(0, 0), (0, 1), (0, 2), (0, 3), (1, 3), (2, 3)];
+        grid.set((0, 2), Some(30));
+        let verification = verify(&grid, &path);
+        assert_eq!((verification.cost, verification.optimum, verification.gap), (Some(34), Some(21), Some(13)));
+    }
+
+    #[test]
+    fn printed_paths_are_read_back() {
+        let grid = tiny_grid(6, 6, 0.0, false, 4);
+        // Serpentin de 36 cellules : l'affichage revient à la ligne toutes les 6 cellules
+        let path: Vec<Coord> = (0..6).flat_map(|r| (0..6).map(move |c| (r, if r % 2 == 0 { c } else { 5 - c }))).collect();
+        for movement in MOVEMENTS {
+            let cost = path.windows(2).map(|pair| movement.step_cost(&grid, pair[0], pair[1])).sum();
+            let printed = path_details("MINIMUM", cost, &path, &grid, movement);
+            assert_eq!(parse_path(&printed), Ok(path.clone()));
+        }
+        assert_eq!(parse_path("(0,0)->(0,1)\n->(1,1)"), Ok(vec![(0, 0), (0, 1), (1, 1)]));
+        assert_eq!(parse_path(" ( 2 , 3 ) "), Ok(vec![(2, 3)]));
+    }
+
+    #[test]
+    fn malformed_paths_are_rejected() {
+        assert_eq!(parse_path(" \n"), Err("the path is empty".to_string()));
+        assert_eq!(parse_path("(0,0)->0,1"), Err("expected (r,c) but got '0,1'".to_string()));
+        assert_eq!(parse_path("(0,0)->(1)"), Err("expected (r,c) but got '(1)'".to_string()));
+        assert!(parse_path("(0,0)->(x,1)").unwrap_err().starts_with("invalid row in '(x,1)'"));
+        assert!(parse_path("(0,0)->(1,-1)").unwrap_err().starts_with("invalid column in '(1,-1)'"));
+        assert_eq!(parse_path("(0,0)->->(0,1)"), Err("expected (r,c) but got ''".to_string()));
+    }
+}